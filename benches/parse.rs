@@ -0,0 +1,96 @@
+//! Measures how long parsing takes and how much it allocates for long patterns.
+//!
+//! Run with `cargo bench --bench parse`.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    hint::black_box,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+
+use rustex::parser::Parser;
+
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+static LIVE_BYTES: AtomicUsize = AtomicUsize::new(0);
+static PEAK_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+
+        let live = LIVE_BYTES.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+        PEAK_BYTES.fetch_max(live, Ordering::Relaxed);
+
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        LIVE_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+const ITERATIONS: u32 = 200;
+
+fn main() {
+    let cases = [
+        ("modifiers", "ab*c+d?e{2,3}".repeat(200)),
+        ("groups", "(a(?:b|c)d)".repeat(200)),
+        ("sets", "[abc][^de]x".repeat(200)),
+        (
+            "alternation",
+            (0..200).map(|i| format!("w{}", i)).collect::<Vec<_>>().join("|"),
+        ),
+    ];
+
+    let parser = Parser::new();
+
+    println!(
+        "{:<12} {:>8} {:>14} {:>14} {:>12} {:>12}",
+        "pattern", "chars", "allocs/parse", "bytes/parse", "peak bytes", "time/parse"
+    );
+    for (name, pattern) in &cases {
+        // Warm up (and make sure the pattern is valid) while measuring how much memory a single parse needs at once.
+        let live = LIVE_BYTES.load(Ordering::Relaxed);
+        PEAK_BYTES.store(live, Ordering::Relaxed);
+        parser.parse_str(pattern).expect("bench patterns should parse");
+        let peak = PEAK_BYTES.load(Ordering::Relaxed) - live;
+
+        let (allocations, bytes) = (
+            ALLOCATIONS.load(Ordering::Relaxed),
+            ALLOCATED_BYTES.load(Ordering::Relaxed),
+        );
+        let start = Instant::now();
+
+        for _ in 0..ITERATIONS {
+            black_box(
+                parser
+                    .parse_str(black_box(pattern))
+                    .expect("bench patterns should parse"),
+            );
+        }
+
+        let elapsed = start.elapsed() / ITERATIONS;
+        let allocations = (ALLOCATIONS.load(Ordering::Relaxed) - allocations) / ITERATIONS as usize;
+        let bytes = (ALLOCATED_BYTES.load(Ordering::Relaxed) - bytes) / ITERATIONS as usize;
+
+        println!(
+            "{:<12} {:>8} {:>14} {:>14} {:>12} {:>12?}",
+            name,
+            pattern.chars().count(),
+            allocations,
+            bytes,
+            peak,
+            elapsed
+        );
+    }
+}
@@ -45,11 +45,15 @@ main() {
   rx 'f(?<wut>o){2}' <(echo 'afoobar')
   t -e 'foo' -e 'bar' <(echo 'afoobar')
   FILE=$(mktemp) && echo 'foobar' > "$FILE" && t foo "$FILE"
+  PAT=$(mktemp) && FILE=$(mktemp) && printf 'fo+\n' > "$PAT" && echo $'foo bar\nbaz' > "$FILE" && t -f "$PAT" "$FILE"
+  FILE=$(mktemp) && echo $'foo bar\nbaz' > "$FILE" && t -e 'baz' "$FILE"
   DIR=$(mktemp -d) && echo $'foo\nfoobar\nbarfoo' > "$DIR/file1" && echo 'barbaz' > "$DIR/file2" && t '(foo|bar)' "$DIR/file1" "$DIR/file2"
   DIR=$(mktemp -d) && echo $'foo\nfoobar\nbarfoo' > "$DIR/file1" && echo 'barbaz' > "$DIR/file2" && t '(foo|bar)' "$DIR"
   t 'hellow?world' <(echo 'helloworld')
   t 'hello(w?)world' <(echo 'helloworld')
-  rx 'h(?<1>el)lo (?<2>wo)rld' -r '$1 $2' <(echo 'hello world!')
+  DIR=$(mktemp -d) && mkdir "$DIR/sub" && echo 'foo' > "$DIR/a.rs" && echo 'foo' > "$DIR/sub/b.txt" && t -g '*.rs' foo "$DIR"
+  t 'a(b)' <(echo 'a(b)c') && rx --syntax bre 'a(b)' <(echo 'a(b)c')
+  rx 'h(el)lo (?<second>wo)rld' -r '$1 ${second}' <(echo 'hello world!')
 }
 
 main "$@"
\ No newline at end of file
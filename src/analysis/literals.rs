@@ -0,0 +1,302 @@
+use crate::parser::{Node, NodeVal, ParseResult};
+
+/// Literals that every match of a pattern is guaranteed to contain.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Literals {
+    /// Every match starts with one of these (empty if no prefix is required).
+    pub prefixes: Vec<String>,
+
+    /// Every match ends with one of these (empty if no suffix is required).
+    pub suffixes: Vec<String>,
+
+    /// Every match contains all of these.
+    pub inner: Vec<String>,
+}
+
+impl Literals {
+    /// Returns the first position in `input` where a match could start, or `None` if the input can't match.
+    pub fn first_candidate(&self, input: &str) -> Option<usize> {
+        Candidates::new(self, input.as_bytes(), 0).next(self, input.as_bytes(), 0)
+    }
+}
+
+/// Tracks where a match could start while an input is searched from left to right.
+///
+/// The inner literals and suffixes are only looked for once, and a prefix is only looked for again once the search has
+/// moved past its last occurrence, so finding every candidate in an input takes linear time.
+#[derive(Debug, Clone)]
+pub(crate) struct Candidates {
+    // Where the inner literals and suffixes were looked for from, and whether they were found.
+    checked_from: usize,
+    possible: bool,
+
+    // Where each prefix was last looked for from, and where it was found.
+    prefixes: Vec<(usize, Option<usize>)>,
+}
+
+impl Candidates {
+    /// Starts tracking the candidates in `input` from byte offset `start` on.
+    pub fn new(literals: &Literals, input: &[u8], start: usize) -> Self {
+        let rest = &input[start..];
+
+        let possible = literals.inner.iter().all(|literal| find(rest, literal).is_some())
+            && (literals.suffixes.is_empty() || literals.suffixes.iter().any(|literal| find(rest, literal).is_some()));
+
+        Candidates {
+            checked_from: start,
+            possible,
+            prefixes: literals
+                .prefixes
+                .iter()
+                .map(|literal| (start, find(rest, literal).map(|pos| start + pos)))
+                .collect(),
+        }
+    }
+
+    /// Returns the first position at or after `start` where a match could start, or `None` if there isn't one.
+    pub fn next(&mut self, literals: &Literals, input: &[u8], start: usize) -> Option<usize> {
+        if start < self.checked_from {
+            *self = Candidates::new(literals, input, start);
+        }
+
+        if !self.possible {
+            return None;
+        }
+
+        if literals.prefixes.is_empty() {
+            return Some(start);
+        }
+
+        for ((searched_from, found), literal) in self.prefixes.iter_mut().zip(&literals.prefixes) {
+            if *searched_from > start || matches!(found, Some(pos) if *pos < start) {
+                *searched_from = start;
+                *found = find(&input[start..], literal).map(|pos| start + pos);
+            }
+        }
+
+        self.prefixes.iter().filter_map(|(_, found)| *found).min()
+    }
+}
+
+fn find(input: &[u8], literal: &str) -> Option<usize> {
+    memchr::memmem::find(input, literal.as_bytes())
+}
+
+/// Computes the literals every match of `parsed` must contain.
+pub fn extract_literals(parsed: &ParseResult) -> Literals {
+    let info = match &parsed.head {
+        None => return Literals::default(),
+        Some(head) => chain_info(head),
+    };
+
+    // A set that includes the empty string doesn't require anything.
+    let required = |literals: Option<&Vec<String>>| match literals {
+        Some(literals) if literals.iter().all(|literal| !literal.is_empty()) => literals.clone(),
+        _ => vec![],
+    };
+
+    // Literals that are part of a longer required literal don't add anything.
+    let inner = info
+        .inner
+        .iter()
+        .filter(|literal| {
+            !info
+                .inner
+                .iter()
+                .any(|other| other.len() > literal.len() && other.contains(literal.as_str()))
+        })
+        .cloned()
+        .collect();
+
+    Literals {
+        prefixes: required(info.prefixes()),
+        suffixes: required(info.suffixes()),
+        inner,
+    }
+}
+
+// Cross products of literal sets are dropped once they'd hold more than this many literals.
+const MAX_LITERALS: usize = 32;
+
+/// What's known about the strings a sub-expression can match.
+#[derive(Debug, Default, Clone)]
+struct Info {
+    /// Every match is exactly one of these.
+    exact: Option<Vec<String>>,
+
+    prefixes: Option<Vec<String>>,
+    suffixes: Option<Vec<String>>,
+    inner: Vec<String>,
+}
+
+impl Info {
+    fn exact(literals: Vec<String>) -> Self {
+        let inner = match literals.as_slice() {
+            [literal] if !literal.is_empty() => vec![literal.clone()],
+            _ => vec![],
+        };
+
+        Info {
+            exact: Some(literals),
+            inner,
+            ..Default::default()
+        }
+    }
+
+    fn prefixes(&self) -> Option<&Vec<String>> {
+        self.exact.as_ref().or(self.prefixes.as_ref())
+    }
+
+    fn suffixes(&self) -> Option<&Vec<String>> {
+        self.exact.as_ref().or(self.suffixes.as_ref())
+    }
+
+    /// Combines what's known about two sub-expressions that match one after the other.
+    fn concat(&self, other: &Info) -> Info {
+        let exact = match (&self.exact, &other.exact) {
+            (Some(left), Some(right)) => cross(left, right),
+            _ => None,
+        };
+
+        let prefixes = match (&self.exact, other.prefixes()) {
+            (Some(left), Some(right)) => cross(left, right).or_else(|| Some(left.clone())),
+            (Some(left), None) => Some(left.clone()),
+            (None, _) => self.prefixes.clone(),
+        };
+
+        let suffixes = match (self.suffixes(), &other.exact) {
+            (Some(left), Some(right)) => cross(left, right).or_else(|| Some(right.clone())),
+            (None, Some(right)) => Some(right.clone()),
+            (_, None) => other.suffixes.clone(),
+        };
+
+        let mut inner = self.inner.clone();
+        let mut push_inner = |literal: &String| {
+            if !literal.is_empty() && !inner.contains(literal) {
+                inner.push(literal.clone());
+            }
+        };
+
+        other.inner.iter().for_each(&mut push_inner);
+
+        // A single required literal that spans both sides is longer than either side's own.
+        for literals in [&exact, &prefixes, &suffixes].into_iter().flatten() {
+            if let [literal] = literals.as_slice() {
+                push_inner(literal);
+            }
+        }
+
+        Info {
+            exact,
+            prefixes,
+            suffixes,
+            inner,
+        }
+    }
+}
+
+fn chain_info(head: &Node) -> Info {
+    head.iter().fold(Info::exact(vec![String::new()]), |info, node| {
+        info.concat(&node_info(node))
+    })
+}
+
+/// Computes what's known about a single node (ignoring `next`).
+fn node_info(node: &Node) -> Info {
+    match &node.val {
+        NodeVal::Word(word) => Info::exact(vec![word.clone()]),
+        NodeVal::Set { set, inverted: false } if set.len() <= MAX_LITERALS => {
+            Info::exact(set.iter().map(char::to_string).collect())
+        }
+        NodeVal::Start | NodeVal::End | NodeVal::GroupEnd { .. } | NodeVal::IterationEnd { .. } => {
+            Info::exact(vec![String::new()])
+        }
+        NodeVal::Group { group, .. } => chain_info(group),
+        NodeVal::Optional(node) => match chain_info(node).exact {
+            None => Info::default(),
+            Some(mut exact) => {
+                exact.push(String::new());
+                Info::exact(exact)
+            }
+        },
+        NodeVal::OneOrMore { node, .. } => repeated(&chain_info(node)),
+        NodeVal::RepetitionRange { min, max, node } if *min > 0 => {
+            let body = chain_info(node);
+
+            // An exact number of repetitions of an exact body is exact too.
+            match (&body.exact, *max == Some(*min)) {
+                (Some(exact), true) => {
+                    let mut res = Some(vec![String::new()]);
+                    for _ in 0..*min {
+                        res = res.and_then(|res| cross(&res, exact));
+                    }
+
+                    res.map_or_else(|| repeated(&body), Info::exact)
+                }
+                _ => repeated(&body),
+            }
+        }
+        NodeVal::Or(branches) => {
+            let branches = branches.iter().map(|branch| chain_info(branch)).collect::<Vec<_>>();
+
+            let union = |literals: &dyn Fn(&Info) -> Option<&Vec<String>>| {
+                let mut res: Vec<String> = vec![];
+                for branch in &branches {
+                    for literal in literals(branch)? {
+                        if !res.contains(literal) {
+                            res.push(literal.clone());
+                        }
+                    }
+                }
+
+                Some(res).filter(|res| res.len() <= MAX_LITERALS)
+            };
+
+            Info {
+                exact: union(&|branch| branch.exact.as_ref()),
+                prefixes: union(&Info::prefixes),
+                suffixes: union(&Info::suffixes),
+                inner: branches[0]
+                    .inner
+                    .iter()
+                    .filter(|literal| branches[1..].iter().all(|branch| branch.inner.contains(literal)))
+                    .cloned()
+                    .collect(),
+            }
+        }
+        NodeVal::Any
+        | NodeVal::Set { .. }
+        | NodeVal::ZeroOrMore { .. }
+        | NodeVal::RepetitionRange { .. }
+        | NodeVal::Backreference(_)
+        | NodeVal::Byte(_) => Info::default(),
+    }
+}
+
+/// What's known about one or more repetitions of a body.
+fn repeated(body: &Info) -> Info {
+    Info {
+        exact: None,
+        prefixes: body.prefixes().cloned(),
+        suffixes: body.suffixes().cloned(),
+        inner: body.inner.clone(),
+    }
+}
+
+fn cross(left: &[String], right: &[String]) -> Option<Vec<String>> {
+    if left.len() * right.len() > MAX_LITERALS {
+        return None;
+    }
+
+    let mut res = vec![];
+    for left in left {
+        for right in right {
+            let literal = format!("{}{}", left, right);
+            if !res.contains(&literal) {
+                res.push(literal);
+            }
+        }
+    }
+
+    Some(res)
+}
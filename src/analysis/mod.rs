@@ -0,0 +1,378 @@
+use core::fmt;
+use std::sync::Arc;
+
+use indexmap::IndexSet;
+
+use crate::parser::{Node, NodeVal, ParseResult, Span};
+
+mod literals;
+pub(crate) use literals::Candidates;
+pub use literals::{extract_literals, Literals};
+
+/// How quickly the work needed to reject an input grows with the input's length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Polynomial,
+    Exponential,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HazardKind {
+    /// A repeated sub-expression contains another repetition (at `inner`) that can match a whole iteration on its
+    /// own, so a run of input can be split between iterations in many ways, like `(a+)+`.
+    NestedQuantifier { inner: Span },
+
+    /// A repeated alternation has two branches (at `left` and `right`) that can match the same input, like `(a|a)*`
+    /// or `([ab]|b)+`.
+    OverlappingAlternation { left: Span, right: Span },
+
+    /// Two repetitions in a row (at `first` and `second`) can match the same input, like `a+[ab]+` or `.*.*`.
+    AdjacentQuantifiers { first: Span, second: Span },
+}
+
+/// A sub-expression that can make the executor's frontier grow out of control on inputs that don't match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hazard {
+    pub kind: HazardKind,
+    pub severity: Severity,
+
+    /// The whole offending sub-expression.
+    pub span: Span,
+}
+
+impl fmt::Display for Hazard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.severity {
+            Severity::Polynomial => write!(f, "polynomial")?,
+            Severity::Exponential => write!(f, "exponential")?,
+        }
+
+        match &self.kind {
+            HazardKind::NestedQuantifier { inner } => {
+                write!(f, " backtracking from nested quantifier {} inside {}", inner, self.span)
+            }
+            HazardKind::OverlappingAlternation { left, right } => write!(
+                f,
+                " backtracking from overlapping alternatives {} and {} inside {}",
+                left, right, self.span
+            ),
+            HazardKind::AdjacentQuantifiers { first, second } => {
+                write!(f, " backtracking from adjacent quantifiers {} and {}", first, second)
+            }
+        }
+    }
+}
+
+/// Looks for sub-expressions that are prone to catastrophic backtracking, in pattern order.
+///
+/// This is a conservative heuristic: every pattern it reports can be slow on some input, but it doesn't try to prove
+/// that the slow input exists and it can miss hazards hidden behind backreferences or long literal sequences.
+pub fn analyze(parsed: &ParseResult) -> Vec<Hazard> {
+    let mut hazards = vec![];
+
+    if let Some(head) = &parsed.head {
+        head.walk(&mut |node| {
+            if let Some((body, unbounded)) = repetition_body(&node.val) {
+                analyze_repetition(node, body, unbounded, &mut hazards);
+            }
+
+            analyze_adjacent(node, &mut hazards);
+        });
+    }
+
+    hazards.sort_by_key(|hazard| hazard.span.start);
+
+    hazards
+}
+
+fn analyze_repetition(node: &Node, body: &Node, unbounded: bool, hazards: &mut Vec<Hazard>) {
+    // A bounded outer repetition caps the number of ways to split the input at a polynomial.
+    let severity = match unbounded {
+        true => Severity::Exponential,
+        false => Severity::Polynomial,
+    };
+
+    if let Some(inner) = find_splittable_repetition(body) {
+        hazards.push(Hazard {
+            kind: HazardKind::NestedQuantifier { inner },
+            severity,
+            span: node.span,
+        });
+    }
+
+    if let Some((left, right)) = find_overlapping_alternation(body, &first_chars(body)) {
+        hazards.push(Hazard {
+            kind: HazardKind::OverlappingAlternation { left, right },
+            severity,
+            span: node.span,
+        });
+    }
+}
+
+fn analyze_adjacent(node: &Node, hazards: &mut Vec<Hazard>) {
+    let body = match repetition_body(&node.val) {
+        Some((body, true)) => body,
+        _ => return,
+    };
+
+    let first = first_chars(body);
+
+    // Anything that can match empty between the two repetitions doesn't separate them.
+    for next in node.iter().skip(1) {
+        match repetition_body(&next.val) {
+            Some((next_body, true)) if first.overlaps(&first_chars(next_body)) => {
+                hazards.push(Hazard {
+                    kind: HazardKind::AdjacentQuantifiers {
+                        first: node.span,
+                        second: next.span,
+                    },
+                    severity: Severity::Polynomial,
+                    span: Span {
+                        start: node.span.start,
+                        end: next.span.end,
+                    },
+                });
+
+                return;
+            }
+            _ if next.val.matches_empty() => {}
+            _ => return,
+        }
+    }
+}
+
+/// Returns the body of a value that can repeat more than once and whether the repetition is unbounded.
+fn repetition_body(val: &NodeVal) -> Option<(&Arc<Node>, bool)> {
+    match val {
+        NodeVal::ZeroOrMore { node, .. } | NodeVal::OneOrMore { node, .. } => Some((node, true)),
+        NodeVal::RepetitionRange { max: None, node, .. } => Some((node, true)),
+        NodeVal::RepetitionRange {
+            max: Some(max), node, ..
+        } if *max > 1 => Some((node, false)),
+        _ => None,
+    }
+}
+
+/// Finds a repetition in `chain` that everything else in the chain lets match the whole chain on its own.
+fn find_splittable_repetition(chain: &Node) -> Option<Span> {
+    let nodes = chain.iter().collect::<Vec<_>>();
+
+    for (i, node) in nodes.iter().enumerate() {
+        let rest_matches_empty = nodes
+            .iter()
+            .enumerate()
+            .all(|(j, other)| i == j || other.val.matches_empty());
+
+        if !rest_matches_empty {
+            continue;
+        }
+
+        let found = match &node.val {
+            val if repetition_body(val).is_some_and(|(body, _)| !body.matches_empty()) => Some(node.span),
+            NodeVal::Group { group, .. } | NodeVal::Optional(group) => find_splittable_repetition(group),
+            NodeVal::Or(branches) => branches.iter().find_map(|branch| find_splittable_repetition(branch)),
+            _ => None,
+        };
+
+        if found.is_some() {
+            return found;
+        }
+    }
+
+    None
+}
+
+/// Finds an alternation in `chain` (outside of nested repetitions) with two branches that can match the same input.
+///
+/// `follow` is the set of chars that can come after `chain`.
+fn find_overlapping_alternation(chain: &Node, follow: &CharSet) -> Option<(Span, Span)> {
+    for node in chain.iter() {
+        // The chars that can come after this node.
+        let node_follow = match &node.next {
+            None => follow.clone(),
+            Some(next) if next.matches_empty() => first_chars(next).union(follow),
+            Some(next) => first_chars(next),
+        };
+
+        let found = match &node.val {
+            NodeVal::Or(branches) => {
+                let mut found = None;
+                'outer: for (i, left) in branches.iter().enumerate() {
+                    for right in &branches[i + 1..] {
+                        if branches_overlap(left, right, &node_follow) {
+                            found = Some((branch_span(left), branch_span(right)));
+                            break 'outer;
+                        }
+                    }
+                }
+
+                found.or_else(|| {
+                    branches
+                        .iter()
+                        .find_map(|branch| find_overlapping_alternation(branch, &node_follow))
+                })
+            }
+            NodeVal::Group { group, .. } | NodeVal::Optional(group) => {
+                find_overlapping_alternation(group, &node_follow)
+            }
+            _ => None,
+        };
+
+        if found.is_some() {
+            return found;
+        }
+    }
+
+    None
+}
+
+fn branch_span(branch: &Node) -> Span {
+    Span {
+        start: branch.span.start,
+        end: branch.iter().last().map_or(branch.span.end, |last| last.span.end),
+    }
+}
+
+/// Whether two alternatives can match the same input, given the chars that can follow them.
+fn branches_overlap(left: &Node, right: &Node, follow: &CharSet) -> bool {
+    let (left, right) = (atoms(left), atoms(right));
+
+    for i in 0.. {
+        match (left.get(i), right.get(i)) {
+            (None, None) => return true,
+
+            // One branch is a prefix of the other, so what follows the shorter one decides if they overlap.
+            (Some(atom), None) | (None, Some(atom)) => {
+                return atom.as_ref().is_none_or(|atom| atom.overlaps(follow));
+            }
+
+            (Some(Some(left)), Some(Some(right))) => {
+                if !left.overlaps(right) {
+                    return false;
+                }
+            }
+
+            // Anything more complex than a single char is assumed to overlap.
+            (Some(_), Some(_)) => return true,
+        }
+    }
+
+    unreachable!("the loop only exits by returning")
+}
+
+/// Breaks a chain into the set of chars each position can match, up to the first node that isn't a single char
+/// (which is `None`).
+fn atoms(chain: &Node) -> Vec<Option<CharSet>> {
+    let mut res = vec![];
+
+    for node in chain.iter() {
+        match &node.val {
+            NodeVal::Word(word) => res.extend(word.chars().map(|ch| Some(CharSet::single(ch)))),
+            NodeVal::Any => res.push(Some(CharSet::any())),
+            NodeVal::Set { set, inverted } => res.push(Some(CharSet {
+                chars: set.clone(),
+                inverted: *inverted,
+            })),
+            NodeVal::Start | NodeVal::End => {}
+            NodeVal::Group { group, .. } if group.iter().all(|node| node.val.children().is_empty()) => {
+                res.extend(atoms(group))
+            }
+            _ => {
+                res.push(None);
+                break;
+            }
+        }
+    }
+
+    res
+}
+
+/// Returns the chars that can start a match of `chain`.
+fn first_chars(chain: &Node) -> CharSet {
+    let mut first = CharSet::none();
+
+    for node in chain.iter() {
+        let node_first = match &node.val {
+            NodeVal::Word(word) => word.chars().next().map_or_else(CharSet::none, CharSet::single),
+            NodeVal::Any | NodeVal::Backreference(_) | NodeVal::Byte(_) => CharSet::any(),
+            NodeVal::Set { set, inverted } => CharSet {
+                chars: set.clone(),
+                inverted: *inverted,
+            },
+            NodeVal::ZeroOrMore { node, .. }
+            | NodeVal::OneOrMore { node, .. }
+            | NodeVal::Optional(node)
+            | NodeVal::RepetitionRange { node, .. } => first_chars(node),
+            NodeVal::Group { group, .. } => first_chars(group),
+            NodeVal::Or(branches) => branches
+                .iter()
+                .fold(CharSet::none(), |first, branch| first.union(&first_chars(branch))),
+            NodeVal::Start | NodeVal::End | NodeVal::GroupEnd { .. } | NodeVal::IterationEnd { .. } => CharSet::none(),
+        };
+
+        first = first.union(&node_first);
+
+        if !node.val.matches_empty() {
+            break;
+        }
+    }
+
+    first
+}
+
+/// A set of chars, which matches everything but `chars` when it's inverted.
+#[derive(Debug, Clone)]
+struct CharSet {
+    chars: IndexSet<char>,
+    inverted: bool,
+}
+
+impl CharSet {
+    fn none() -> Self {
+        CharSet {
+            chars: IndexSet::new(),
+            inverted: false,
+        }
+    }
+
+    fn any() -> Self {
+        CharSet {
+            chars: IndexSet::new(),
+            inverted: true,
+        }
+    }
+
+    fn single(ch: char) -> Self {
+        CharSet {
+            chars: IndexSet::from([ch]),
+            inverted: false,
+        }
+    }
+
+    fn union(&self, other: &CharSet) -> CharSet {
+        match (self.inverted, other.inverted) {
+            (false, false) => CharSet {
+                chars: self.chars.union(&other.chars).copied().collect(),
+                inverted: false,
+            },
+            (false, true) => CharSet {
+                chars: other.chars.difference(&self.chars).copied().collect(),
+                inverted: true,
+            },
+            (true, false) => other.union(self),
+            (true, true) => CharSet {
+                chars: self.chars.intersection(&other.chars).copied().collect(),
+                inverted: true,
+            },
+        }
+    }
+
+    fn overlaps(&self, other: &CharSet) -> bool {
+        match (self.inverted, other.inverted) {
+            (false, false) => self.chars.iter().any(|ch| other.chars.contains(ch)),
+            (false, true) => self.chars.iter().any(|ch| !other.chars.contains(ch)),
+            (true, false) => other.overlaps(self),
+            (true, true) => true,
+        }
+    }
+}
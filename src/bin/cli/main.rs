@@ -1,9 +1,9 @@
 use clap::{CommandFactory, Parser};
 use termcolor::{self};
 
-use std::{fs, io};
+use std::{fs, io, path};
 
-use rustex::{executor, parser, replace};
+use rustex::{analysis, executor, parser, replace};
 
 mod error;
 use error::Error;
@@ -17,7 +17,7 @@ use matcher::Matcher;
 #[derive(clap::Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
-    /// The regex pattern to match.
+    /// The regex pattern to match (leave it out when patterns are given with -e or -f).
     #[arg(index = 1, required = false)]
     pattern: Option<String>,
 
@@ -26,12 +26,47 @@ struct Args {
     filenames: Vec<String>,
 
     /// Patterns to search for.
-    #[arg(short = 'e', long, conflicts_with = "pattern")]
+    #[arg(short = 'e', long)]
     expressions: Vec<String>,
 
+    /// Read the pattern from a file.
+    ///
+    /// The whole file is a single pattern, except that one trailing newline is ignored.
+    #[arg(short = 'f', long = "file", conflicts_with = "expressions")]
+    pattern_file: Option<String>,
+
     /// Replacement spec.
     #[arg(short = 'r', long)]
     replace: Option<String>,
+
+    /// The syntax the patterns are written in.
+    #[arg(long, value_enum, default_value_t = SyntaxArg::Rustex)]
+    syntax: SyntaxArg,
+
+    /// Only search files found in directories whose paths match one of these globs.
+    ///
+    /// Globs without a '/' are matched against file names; others are matched against the whole path.
+    #[arg(short = 'g', long = "glob")]
+    globs: Vec<String>,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+enum SyntaxArg {
+    Rustex,
+    Bre,
+    Ere,
+    Glob,
+}
+
+impl From<SyntaxArg> for parser::Syntax {
+    fn from(arg: SyntaxArg) -> Self {
+        match arg {
+            SyntaxArg::Rustex => parser::Syntax::Rustex,
+            SyntaxArg::Bre => parser::Syntax::PosixBre,
+            SyntaxArg::Ere => parser::Syntax::PosixEre,
+            SyntaxArg::Glob => parser::Syntax::Glob,
+        }
+    }
 }
 
 #[tokio::main]
@@ -42,7 +77,14 @@ pub async fn main() -> Result<(), u32> {
 }
 
 async fn maine() -> Result<(), Error> {
-    let args = Args::parse();
+    let mut args = Args::parse();
+
+    // Patterns given with -e or -f leave no pattern positional, so the first one is really a path.
+    if !args.expressions.is_empty() || args.pattern_file.is_some() {
+        if let Some(path) = args.pattern.take() {
+            args.filenames.insert(0, path);
+        }
+    }
 
     let (filenames, read_stdin) = {
         let mut filenames = vec![];
@@ -74,12 +116,43 @@ async fn maine() -> Result<(), Error> {
     };
 
     let expressions = {
-        let parser = parser::Parser::new();
+        let parser = parser::Parser::with_syntax(args.syntax.into());
 
-        args.pattern
+        let mut expressions = args
+            .pattern
             .map_or_else(|| args.expressions, |pattern| vec![pattern])
             .iter()
             .map(|expr| parser.parse_str(expr))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if let Some(path) = &args.pattern_file {
+            let pattern = fs::read_to_string(path)?;
+            let pattern = pattern.strip_suffix('\n').map_or(pattern.as_str(), |pattern| {
+                pattern.strip_suffix('\r').unwrap_or(pattern)
+            });
+
+            expressions.push(parser.parse_str(pattern)?);
+        }
+
+        expressions
+    };
+
+    for expr in &expressions {
+        for warning in expr.warnings() {
+            eprintln!("warning: {}", warning);
+        }
+
+        for hazard in analysis::analyze(expr) {
+            eprintln!("warning: {}", hazard);
+        }
+    }
+
+    let globs = {
+        let parser = parser::Parser::with_syntax(parser::Syntax::Glob);
+
+        args.globs
+            .iter()
+            .map(|glob| parser.parse_str(glob).map(|parsed| (glob.contains('/'), parsed)))
             .collect::<Result<Vec<_>, _>>()?
     };
 
@@ -89,8 +162,13 @@ async fn maine() -> Result<(), Error> {
         // Add literal files.
         for filename in filenames {
             // The filename might actually be a dir, so we need to transform a single path into multiple file specs.
-            for spec in get_filespecs_from_path(filename)? {
-                files.push(spec);
+            for (path, found_in_dir) in get_paths_from_path(filename)? {
+                if found_in_dir && !matches_globs(&globs, &path).await? {
+                    continue;
+                }
+
+                let file = fs::File::open(&path)?;
+                files.push(FileInput::File(path, file));
             }
         }
 
@@ -122,8 +200,9 @@ enum FileInput {
     Stdin(io::Stdin),
 }
 
-fn get_filespecs_from_path(filename: &str) -> Result<Vec<FileInput>, Error> {
-    fn rec(filename: &str, files: &mut Vec<FileInput>) -> Result<(), Error> {
+/// Returns the files at `filename` along with whether each was found by walking a directory.
+fn get_paths_from_path(filename: &str) -> Result<Vec<(String, bool)>, Error> {
+    fn rec(filename: &str, found_in_dir: bool, paths: &mut Vec<(String, bool)>) -> Result<(), Error> {
         let metadata = fs::metadata(filename)?;
 
         if metadata.is_dir() {
@@ -132,18 +211,39 @@ fn get_filespecs_from_path(filename: &str) -> Result<Vec<FileInput>, Error> {
                 let entry = entry?;
                 let path = entry.path();
 
-                rec(path.to_str().unwrap(), files)?;
+                rec(path.to_str().unwrap(), true, paths)?;
             }
         } else {
-            let file = fs::File::open(filename)?;
-            files.push(FileInput::File(filename.into(), file));
+            paths.push((filename.into(), found_in_dir));
         }
 
         Ok(())
     }
 
-    let mut files = vec![];
-    rec(filename, &mut files)?;
+    let mut paths = vec![];
+    rec(filename, false, &mut paths)?;
+
+    Ok(paths)
+}
+
+async fn matches_globs(globs: &[(bool, parser::ParseResult)], path: &str) -> Result<bool, Error> {
+    if globs.is_empty() {
+        return Ok(true);
+    }
+
+    let file_name = path::Path::new(path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(path);
+
+    let executor = executor::Executor::new();
+    for (match_whole_path, glob) in globs {
+        let input = if *match_whole_path { path } else { file_name };
+
+        if executor.exec(glob, input).await?.is_some() {
+            return Ok(true);
+        }
+    }
 
-    Ok(files)
+    Ok(false)
 }
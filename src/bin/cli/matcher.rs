@@ -26,31 +26,29 @@ impl<W: io::Write> Matcher<W> {
                 FileInput::Stdin(stdin) => (Box::new(stdin), "stdin".into(), true),
             };
 
-            let mut executor = executor::Executor::new();
+            let executor = executor::Executor::new();
             let mut reader = io::BufReader::new(file_handle);
 
             let mut line_num = 0;
             let mut has_printed_file_info = false;
             loop {
-                let mut line = String::new();
+                // Lines are matched as bytes since they aren't necessarily valid UTF-8.
+                let mut line = Vec::new();
 
-                match reader.read_line(&mut line) {
+                match reader.read_until(b'\n', &mut line) {
                     Err(err) => return Err(Error::from(err)),
                     Ok(0) => break,
                     _ => {}
                 };
 
                 line_num += 1;
-                let line_bytes = line.bytes().collect::<Vec<_>>();
 
                 for expr in &args.expressions {
-                    let exec_res = executor.exec(expr, &line).await?;
-                    if exec_res.is_none() {
+                    let results = executor.exec_bytes_iter(expr, &line).collect::<Vec<_>>();
+                    if results.is_empty() {
                         continue;
                     }
 
-                    let res = exec_res.unwrap();
-
                     // Write filename info if applicable and if we haven't already done it.
                     if should_print_file_info && !has_printed_file_info {
                         self.printer.print_file_start(&file_name.bytes().collect::<Vec<_>>())?;
@@ -65,12 +63,14 @@ impl<W: io::Write> Matcher<W> {
                     // Write result info.
                     match &args.replace_spec {
                         Some(replace_spec) => {
-                            if let Some(replaced) = replace_spec.perform_replace(&line, &res) {
-                                self.printer.print_replacement(replaced.as_bytes())?;
+                            for res in &results {
+                                if let Some(replaced) = replace_spec.perform_replace_bytes(&line, res) {
+                                    self.printer.print_replacement(&replaced)?;
+                                }
                             }
                         }
                         None => {
-                            self.printer.print_match(&res, &line_bytes)?;
+                            self.printer.print_matches(&results, &line)?;
                         }
                     }
                 }
@@ -38,11 +38,11 @@ impl<W: std::io::Write> OutputPrinter<W> {
             .set_color(&FILENAME_COLOR_SPEC)
             .and_then(|_| self.pr.write(file_name_bytes))
             .and_then(|_| self.pr.reset())
-            .and_then(|_| self.pr.write(&[b'\n']))
+            .and_then(|_| self.pr.write(b"\n"))
     }
 
     pub fn print_file_end(&mut self) -> std::io::Result<usize> {
-        self.pr.write(&[b'\n'])
+        self.pr.write(b"\n")
     }
 
     pub fn print_line_num(&mut self, line_num: usize) -> std::io::Result<usize> {
@@ -50,16 +50,27 @@ impl<W: std::io::Write> OutputPrinter<W> {
             .set_color(&LINE_NUMBER_COLOR_SPEC)
             .and_then(|_| self.pr.write_fmt(format_args!("{:?}", line_num)))
             .and_then(|_| self.pr.reset())
-            .and_then(|_| self.pr.write(&[b':']))
+            .and_then(|_| self.pr.write(b":"))
     }
 
-    pub fn print_match(&mut self, res: &ExecResult, line_bytes: &[u8]) -> std::io::Result<usize> {
-        self.pr
-            .write(&line_bytes[0..res.start])
-            .and_then(|_| self.pr.set_color(&MATCH_COLOR_SPEC))
-            .and_then(|_| self.pr.write(&line_bytes[res.start..res.end + 1]))
-            .and_then(|_| self.pr.reset())
-            .and_then(|_| self.pr.write(&line_bytes[res.end + 1..]))
+    /// Prints a line with every match in `results` (which must be in order and not overlap) highlighted.
+    pub fn print_matches(&mut self, results: &[ExecResult], line_bytes: &[u8]) -> std::io::Result<usize> {
+        let mut written = 0;
+        let mut last_end = 0;
+
+        for res in results {
+            written += self.pr.write(&line_bytes[last_end..res.start])?;
+
+            self.pr.set_color(&MATCH_COLOR_SPEC)?;
+            written += self.pr.write(&line_bytes[res.start..res.end])?;
+            self.pr.reset()?;
+
+            last_end = res.end;
+        }
+
+        written += self.pr.write(&line_bytes[last_end..])?;
+
+        Ok(written)
     }
 
     pub fn print_replacement(&mut self, replacement: &[u8]) -> std::io::Result<usize> {
@@ -67,6 +78,6 @@ impl<W: std::io::Write> OutputPrinter<W> {
             .set_color(&MATCH_COLOR_SPEC)
             .and_then(|_| self.pr.write(replacement))
             .and_then(|_| self.pr.reset())
-            .and_then(|_| self.pr.write(&[b'\n']))
+            .and_then(|_| self.pr.write(b"\n"))
     }
 }
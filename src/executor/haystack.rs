@@ -0,0 +1,44 @@
+/// The input a pattern is matched against.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Haystack<'i> {
+    Str(&'i str),
+    Bytes(&'i [u8]),
+}
+
+impl<'i> Haystack<'i> {
+    pub fn as_bytes(&self) -> &'i [u8] {
+        match self {
+            Haystack::Str(input) => input.as_bytes(),
+            Haystack::Bytes(input) => input,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.as_bytes().len()
+    }
+
+    /// Whether a match can start at `pos` (a str can only be split between chars).
+    pub fn is_boundary(&self, pos: usize) -> bool {
+        match self {
+            Haystack::Str(input) => input.is_char_boundary(pos),
+            Haystack::Bytes(_) => true,
+        }
+    }
+
+    /// Returns the position right after the char (or the byte that isn't part of one) at `pos`.
+    pub fn next_pos(&self, pos: usize) -> usize {
+        pos + decode_char(&self.as_bytes()[pos..]).map_or(1, char::len_utf8)
+    }
+}
+
+/// Decodes the char `bytes` starts with, if they start with valid UTF-8.
+pub(crate) fn decode_char(bytes: &[u8]) -> Option<char> {
+    let bytes = &bytes[..bytes.len().min(4)];
+
+    let valid = match std::str::from_utf8(bytes) {
+        Ok(valid) => valid,
+        Err(err) => std::str::from_utf8(&bytes[..err.valid_up_to()]).ok()?,
+    };
+
+    valid.chars().next()
+}
@@ -0,0 +1,173 @@
+use std::{borrow::Cow, collections::VecDeque};
+
+use crate::{
+    analysis::{Candidates, Literals},
+    parser::ParseResult,
+};
+
+use super::{ExecResult, Executor, Haystack};
+
+/// An iterator over the successive non-overlapping matches of a pattern in an input.
+///
+/// Each search starts where the previous match ended. An empty match right where the previous match ended is skipped
+/// (so `a*` finds `aa` and then an empty match at the end of `aab`, but not one right before `b`), and the search moves
+/// one char (or one byte that isn't part of a char) past every empty match so it can't get stuck.
+pub struct ExecIter<'p, 'i> {
+    executor: Executor,
+    parsed: &'p ParseResult,
+    literals: Cow<'p, Literals>,
+    candidates: Candidates,
+    input: Haystack<'i>,
+
+    // Where the next search starts (past the end of the input once the iterator is done).
+    next_start: usize,
+    last_match_end: Option<usize>,
+}
+
+impl<'p, 'i> ExecIter<'p, 'i> {
+    pub(crate) fn new(
+        executor: Executor,
+        parsed: &'p ParseResult,
+        literals: Cow<'p, Literals>,
+        input: Haystack<'i>,
+    ) -> Self {
+        ExecIter {
+            executor,
+            parsed,
+            candidates: Candidates::new(&literals, input.as_bytes(), 0),
+            literals,
+            input,
+            next_start: 0,
+            last_match_end: None,
+        }
+    }
+}
+
+impl Iterator for ExecIter<'_, '_> {
+    type Item = ExecResult;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.next_start > self.input.len() {
+                return None;
+            }
+
+            // Executing a parsed pattern can't fail.
+            let res = self
+                .executor
+                .exec_from(
+                    self.parsed,
+                    &self.literals,
+                    &mut self.candidates,
+                    self.input,
+                    self.next_start,
+                )
+                .ok()
+                .flatten();
+
+            let res = match res {
+                None => {
+                    self.next_start = self.input.len() + 1;
+                    return None;
+                }
+                Some(res) => res,
+            };
+
+            if res.start != res.end {
+                self.next_start = res.end;
+            } else {
+                self.next_start = self.input.next_pos(res.end);
+
+                if self.last_match_end == Some(res.end) {
+                    continue;
+                }
+            }
+
+            self.last_match_end = Some(res.end);
+
+            return Some(res);
+        }
+    }
+}
+
+/// An iterator over the matches of a pattern starting at every position in an input, including ones that overlap.
+///
+/// By default this yields the match the executor picks at each start (so `aa` matches `aaaa` three times); with
+/// [`OverlappingIter::all_ends`] it yields a match for every distinct end reachable from each start instead, ordered by
+/// start and then end.
+pub struct OverlappingIter<'p, 'i> {
+    executor: Executor,
+    parsed: &'p ParseResult,
+    literals: Cow<'p, Literals>,
+    candidates: Candidates,
+    input: Haystack<'i>,
+    all_ends: bool,
+
+    // The next position to try (past the end of the input once the iterator is done).
+    next_start: usize,
+    pending: VecDeque<ExecResult>,
+}
+
+impl<'p, 'i> OverlappingIter<'p, 'i> {
+    pub(crate) fn new(
+        executor: Executor,
+        parsed: &'p ParseResult,
+        literals: Cow<'p, Literals>,
+        input: Haystack<'i>,
+    ) -> Self {
+        OverlappingIter {
+            executor,
+            parsed,
+            candidates: Candidates::new(&literals, input.as_bytes(), 0),
+            literals,
+            input,
+            all_ends: false,
+            next_start: 0,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Sets whether every end reachable from a start is reported rather than just one (off by default).
+    pub fn all_ends(mut self, all_ends: bool) -> Self {
+        self.all_ends = all_ends;
+        self
+    }
+}
+
+impl Iterator for OverlappingIter<'_, '_> {
+    type Item = ExecResult;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending.is_empty() {
+            if self.next_start > self.input.len() {
+                return None;
+            }
+
+            let start = self.next_start;
+            self.next_start = self.input.next_pos(start);
+
+            // Executing a parsed pattern can't fail.
+            let matches = match self.all_ends {
+                true => self
+                    .executor
+                    .exec_all_at(self.parsed, &self.literals, &mut self.candidates, self.input, start),
+                false => self
+                    .executor
+                    .exec_anchored_at(self.parsed, &self.literals, &mut self.candidates, self.input, start)
+                    .map(Vec::from_iter),
+            }
+            .unwrap_or_default();
+
+            // Keep the first match found for each end.
+            for res in matches {
+                match self.pending.iter().position(|other| other.end >= res.end) {
+                    Some(i) if self.pending[i].end == res.end => {}
+                    Some(i) => self.pending.insert(i, res),
+                    None => self.pending.push_back(res),
+                }
+            }
+        }
+
+        self.pending.pop_front()
+    }
+}
@@ -1,20 +1,36 @@
 use core::fmt;
-use std::{collections::VecDeque, ops::DerefMut, sync::Arc};
+use std::{borrow::Cow, cell::RefCell, collections::VecDeque, sync::Arc};
 
-use async_recursion::async_recursion;
-use tokio::sync::Mutex;
-use tracing::{debug, instrument};
+use crate::{
+    analysis::{self, Candidates},
+    parser::{self, Node, NodeVal},
+    trace::debug,
+};
 
-use crate::parser::{self, Node, NodeVal};
+mod haystack;
+pub(crate) use haystack::Haystack;
+
+mod iter;
+pub use iter::{ExecIter, OverlappingIter};
 
 mod utils;
 use utils::*;
 
+/// A single match of a pattern against an input.
+///
+/// Every capturing group is keyed by its index in `groups` (group 0 is the whole match), and named groups can also be
+/// looked up by name through `names`. Every position is a byte offset into the input (always on a char boundary).
+///
+/// With the `serde` feature enabled this serializes as
+/// `{ "start": usize, "end": usize, "groups": { "<index>": [start, end], ... }, "names": { "<name>": usize, ... } }`,
+/// where `end` is exclusive (so an empty match has `start == end`).
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ExecResult {
     pub start: usize,
     pub end: usize,
-    pub groups: indexmap::IndexMap<String, (usize, usize)>,
+    pub groups: indexmap::IndexMap<usize, (usize, usize)>,
+    pub names: indexmap::IndexMap<String, usize>,
 }
 
 impl ExecResult {
@@ -23,34 +39,29 @@ impl ExecResult {
             start,
             end: 0,
             groups: indexmap::indexmap! {},
+            names: indexmap::indexmap! {},
         }
     }
 
-    fn merge_groups(mut self, other: ExecResult) -> Self {
-        self.groups.extend(other.groups);
-        self
+    /// Returns the span captured by the group with the given index, if it participated in the match.
+    pub fn group(&self, index: usize) -> Option<(usize, usize)> {
+        self.groups.get(&index).copied()
     }
 
-    fn map_options(dest: Option<Self>, src: Option<Self>) -> Option<Self> {
-        match (dest, src) {
-            (None, None) => None,
-            (None, src @ Some(_)) => src,
-            (dest @ Some(_), None) => dest,
-            (Some(src), Some(dest)) => Some(src.merge_groups(dest)),
-        }
+    /// Returns the span captured by the group with the given name, if it participated in the match.
+    pub fn named_group(&self, name: &str) -> Option<(usize, usize)> {
+        self.names.get(name).and_then(|index| self.group(*index))
     }
 }
 
 pub enum ExecError {
     EmptyParseResult,
-    PoisonedNode,
 }
 
 impl ExecError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::EmptyParseResult => write!(f, "cannot execute empty parse result"),
-            Self::PoisonedNode => write!(f, "internal: encountered poisoned node"),
         }
     }
 }
@@ -69,246 +80,438 @@ impl fmt::Debug for ExecError {
 
 impl std::error::Error for ExecError {}
 
-#[derive(Default)]
-pub struct Executor {}
+/// How the executor picks between the matches that start at the leftmost position a match can start.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MatchSemantics {
+    /// The first match in priority order wins (like Perl): alternatives are tried left to right, and greedy
+    /// repetitions prefer as many iterations as possible while lazy ones prefer as few.
+    #[default]
+    LeftmostFirst,
+    /// The longest match wins (like POSIX), with its groups taken from the first way of matching it in priority order.
+    LeftmostLongest,
+}
+
+/// Where the executor looks for a match, relative to where the search starts.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SearchMode {
+    /// A match can start anywhere at or after the search start.
+    #[default]
+    Unanchored,
+    /// A match has to start exactly at the search start.
+    Anchored,
+    /// A match has to start exactly at the search start and run to the end of the input.
+    FullMatch,
+}
+
+#[derive(Default, Clone, Copy)]
+pub struct Executor {
+    semantics: MatchSemantics,
+    mode: SearchMode,
+    match_invalid_utf8: bool,
+}
 
 impl Executor {
     pub fn new() -> Self {
-        Executor {}
+        Executor::default()
+    }
+
+    /// Sets how the match is picked when several start at the same position (leftmost-first by default).
+    pub fn semantics(mut self, semantics: MatchSemantics) -> Self {
+        self.semantics = semantics;
+        self
+    }
+
+    /// Sets where a match can start and end (unanchored by default).
+    pub fn mode(mut self, mode: SearchMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Sets whether `.` and inverted sets also match a single byte that isn't part of valid UTF-8 when matching bytes
+    /// (off by default, so they only match whole chars).
+    pub fn match_invalid_utf8(mut self, match_invalid_utf8: bool) -> Self {
+        self.match_invalid_utf8 = match_invalid_utf8;
+        self
+    }
+
+    /// Matches `parsed` against `input` on the calling thread.
+    pub fn exec_sync(&self, parsed: &parser::ParseResult, input: &str) -> Result<Option<ExecResult>, ExecError> {
+        self.exec_at(parsed, input, 0)
+    }
+
+    /// Matches `parsed` against `input` on the calling thread, starting the search at byte offset `start`.
+    ///
+    /// Positions before `start` are still part of the input, so `^` only matches at 0. There's no match if `start` is
+    /// past the end of `input`.
+    pub fn exec_at(
+        &self,
+        parsed: &parser::ParseResult,
+        input: &str,
+        start: usize,
+    ) -> Result<Option<ExecResult>, ExecError> {
+        self.exec_with_literals(parsed, &analysis::extract_literals(parsed), Haystack::Str(input), start)
+    }
+
+    /// Iterates over the successive non-overlapping matches of `parsed` in `input`.
+    pub fn exec_iter<'p, 'i>(&self, parsed: &'p parser::ParseResult, input: &'i str) -> ExecIter<'p, 'i> {
+        ExecIter::new(
+            *self,
+            parsed,
+            Cow::Owned(analysis::extract_literals(parsed)),
+            Haystack::Str(input),
+        )
+    }
+
+    /// Iterates over the matches of `parsed` starting at every position in `input`, including overlapping ones.
+    pub fn exec_overlapping<'p, 'i>(&self, parsed: &'p parser::ParseResult, input: &'i str) -> OverlappingIter<'p, 'i> {
+        OverlappingIter::new(
+            *self,
+            parsed,
+            Cow::Owned(analysis::extract_literals(parsed)),
+            Haystack::Str(input),
+        )
+    }
+
+    /// Matches `parsed` against `input`, which doesn't have to be UTF-8.
+    ///
+    /// Chars in the pattern match their UTF-8 encoding, while a `\xHH` escape outside of a set matches a single raw
+    /// byte.
+    pub fn exec_bytes(&self, parsed: &parser::ParseResult, input: &[u8]) -> Result<Option<ExecResult>, ExecError> {
+        self.exec_bytes_at(parsed, input, 0)
+    }
+
+    /// Like [`Executor::exec_bytes`], but starts the search at byte offset `start` (see [`Executor::exec_at`]).
+    pub fn exec_bytes_at(
+        &self,
+        parsed: &parser::ParseResult,
+        input: &[u8],
+        start: usize,
+    ) -> Result<Option<ExecResult>, ExecError> {
+        self.exec_with_literals(
+            parsed,
+            &analysis::extract_literals(parsed),
+            Haystack::Bytes(input),
+            start,
+        )
+    }
+
+    /// Iterates over the successive non-overlapping matches of `parsed` in `input`, which doesn't have to be UTF-8.
+    pub fn exec_bytes_iter<'p, 'i>(&self, parsed: &'p parser::ParseResult, input: &'i [u8]) -> ExecIter<'p, 'i> {
+        ExecIter::new(
+            *self,
+            parsed,
+            Cow::Owned(analysis::extract_literals(parsed)),
+            Haystack::Bytes(input),
+        )
+    }
+
+    /// Matches `parsed` against `input` starting the search at `start`, given the literals `parsed` requires (so they
+    /// can be computed once).
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, literals)))]
+    pub(crate) fn exec_with_literals(
+        &self,
+        parsed: &parser::ParseResult,
+        literals: &analysis::Literals,
+        input: Haystack,
+        start: usize,
+    ) -> Result<Option<ExecResult>, ExecError> {
+        if start > input.len() {
+            return Ok(None);
+        }
+
+        let mut candidates = Candidates::new(literals, input.as_bytes(), start);
+
+        self.exec_from(parsed, literals, &mut candidates, input, start)
     }
 
-    #[instrument(skip(self))]
-    pub async fn exec(&mut self, parsed: &parser::ParseResult, input: &str) -> Result<Option<ExecResult>, ExecError> {
-        let executor = Arc::new(ExecutorImpl {
-            input: Arc::new(input.into()),
+    /// Like [`Executor::exec_with_literals`], but continues a search of `input` that's already tracking `candidates`.
+    pub(crate) fn exec_from(
+        &self,
+        parsed: &parser::ParseResult,
+        literals: &analysis::Literals,
+        candidates: &mut Candidates,
+        input: Haystack,
+        start: usize,
+    ) -> Result<Option<ExecResult>, ExecError> {
+        let mut start = start;
+
+        // A search that starts inside a char can't match there.
+        while start < input.len() && !input.is_boundary(start) {
+            if self.mode != SearchMode::Unanchored {
+                return Ok(None);
+            }
+
+            start += 1;
+        }
+
+        loop {
+            // Skip straight to the next place a match could start (or bail if a required literal is missing) before
+            // starting the state machine.
+            start = match candidates.next(literals, input.as_bytes(), start) {
+                None => return Ok(None),
+                Some(candidate) if candidate > start && self.mode != SearchMode::Unanchored => return Ok(None),
+                Some(candidate) => candidate,
+            };
+
+            // The leftmost start with any match wins.
+            if let Some(res) = self.best_at(parsed, input, start)? {
+                return Ok(Some(res));
+            }
+
+            if self.mode != SearchMode::Unanchored {
+                return Ok(None);
+            }
+
+            if start == input.len() {
+                return Ok(None);
+            }
+
+            start = input.next_pos(start);
+        }
+    }
+
+    /// Returns the match of `parsed` that starts exactly at `start`, if there is one.
+    pub(crate) fn exec_anchored_at(
+        &self,
+        parsed: &parser::ParseResult,
+        literals: &analysis::Literals,
+        candidates: &mut Candidates,
+        input: Haystack,
+        start: usize,
+    ) -> Result<Option<ExecResult>, ExecError> {
+        if candidates.next(literals, input.as_bytes(), start) != Some(start) {
+            return Ok(None);
+        }
+
+        self.best_at(parsed, input, start)
+    }
+
+    /// Returns every match of `parsed` that starts exactly at `start`, in priority order.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, literals, candidates)))]
+    pub(crate) fn exec_all_at(
+        &self,
+        parsed: &parser::ParseResult,
+        literals: &analysis::Literals,
+        candidates: &mut Candidates,
+        input: Haystack,
+        start: usize,
+    ) -> Result<Vec<ExecResult>, ExecError> {
+        if candidates.next(literals, input.as_bytes(), start) != Some(start) {
+            return Ok(vec![]);
+        }
+
+        let mut matches = vec![];
+        self.run(parsed, input, start, |res| {
+            matches.push(res);
+            true
+        })?;
+
+        Ok(matches)
+    }
+
+    /// Returns the match of `parsed` that starts exactly at `start` according to the executor's semantics.
+    fn best_at(
+        &self,
+        parsed: &parser::ParseResult,
+        input: Haystack,
+        start: usize,
+    ) -> Result<Option<ExecResult>, ExecError> {
+        let mut best_match: Option<ExecResult> = None;
+        self.run(parsed, input, start, |res| {
+            if !matches!(&best_match, Some(curr_best) if res.end <= curr_best.end) {
+                best_match = Some(res);
+            }
+
+            // Matches are found in priority order, so the first one is the leftmost-first match.
+            self.semantics == MatchSemantics::LeftmostLongest
+        })?;
+
+        Ok(best_match)
+    }
+
+    /// Runs the state machine from `start`, handing every match that starts at `start` to `on_match` in priority order
+    /// until the frontier is exhausted or `on_match` returns `false`.
+    fn run(
+        &self,
+        parsed: &parser::ParseResult,
+        input: Haystack,
+        start: usize,
+        mut on_match: impl FnMut(ExecResult) -> bool,
+    ) -> Result<(), ExecError> {
+        let executor = ExecutorImpl {
+            input: input.as_bytes(),
             n: input.len(),
-            frontier: Arc::new(Mutex::new(VecDeque::new())),
+            raw_bytes: matches!(input, Haystack::Bytes(_)),
+            match_invalid_utf8: self.match_invalid_utf8,
+            frontier: RefCell::new(VecDeque::new()),
+        };
+
+        executor.frontier.borrow_mut().push_front(ExecutorState {
+            res: ExecResult::new(start),
+            node: parsed.head.clone(),
+            cur: start,
         });
 
-        {
-            executor.frontier.lock().await.push_front(ExecutorState {
-                res: None,
-                node: parsed.head.clone(),
-                cur: 0,
-            });
-        }
+        // The frontier is a stack: every state pushes its lower-priority alternatives to the front before exploring its
+        // preferred one, so the most recent alternative is always the next best one.
+        loop {
+            let state = match executor.frontier.borrow_mut().pop_front() {
+                None => break,
+                Some(state) => state,
+            };
 
-        let processing_task: tokio::task::JoinHandle<Option<ExecResult>> = tokio::spawn(async move {
-            let best_match: Arc<Mutex<Option<ExecResult>>> = Arc::new(Mutex::new(None));
-            loop {
-                let executor = executor.clone();
+            debug!(state = format!("{:?}", &state), "popped new state");
 
-                let frontier_clone = {
-                    let mut frontier_lock = executor.frontier.lock().await;
-                    let n = 0..frontier_lock.len();
-                    frontier_lock.drain(n).collect::<Vec<_>>()
-                };
+            if let Some(res) = executor.exec(state.res, state.node, state.cur)? {
+                // A full match has to run to the end of the input, so keep looking for one that does.
+                if self.mode == SearchMode::FullMatch && res.end != input.len() {
+                    continue;
+                }
 
-                let tasks = frontier_clone
-                    .into_iter()
-                    .map(|state| {
-                        let state = state.clone();
-                        let executor = executor.clone();
-                        let best_match = best_match.clone();
-
-                        tokio::spawn(async move {
-                            debug!(state = format!("{:?}", &state), "popped new state");
-
-                            if let Some(res) = executor.exec(state.res, state.node.clone(), state.cur).await? {
-                                let mut best_match = best_match.lock().await;
-                                match best_match.deref_mut() {
-                                    None => {
-                                        best_match.replace(res);
-                                    }
-                                    Some(curr_best) => {
-                                        if res.end > curr_best.end {
-                                            best_match.replace(res);
-                                        }
-                                    }
-                                }
-                            }
-
-                            Ok::<Option<ExecResult>, ExecError>(None)
-                        })
-                    })
-                    .collect::<Vec<_>>();
-
-                if tasks.is_empty() {
+                if !on_match(res) {
                     break;
                 }
-
-                futures::future::join_all(tasks).await;
             }
+        }
 
-            Arc::try_unwrap(best_match).unwrap().into_inner()
-        });
+        Ok(())
+    }
 
-        Ok(processing_task.await.unwrap())
+    /// Matches `parsed` against `input`; this runs [`Executor::exec_sync`] on the current task.
+    #[cfg(feature = "async")]
+    pub async fn exec(&self, parsed: &parser::ParseResult, input: &str) -> Result<Option<ExecResult>, ExecError> {
+        self.exec_sync(parsed, input)
     }
 }
 
 #[derive(Debug, Clone)]
 struct ExecutorState {
-    res: Option<ExecResult>,
+    res: ExecResult,
     node: Option<Arc<Node>>,
     cur: usize,
 }
 
-struct ExecutorImpl {
-    input: Arc<String>,
+struct ExecutorImpl<'input> {
+    input: &'input [u8],
     n: usize,
 
-    frontier: Arc<Mutex<VecDeque<ExecutorState>>>,
+    // Whether byte escapes match raw bytes (rather than the chars with the same code points).
+    raw_bytes: bool,
+    match_invalid_utf8: bool,
+
+    frontier: RefCell<VecDeque<ExecutorState>>,
 }
 
-impl ExecutorImpl {
-    #[instrument(skip(self, res))]
-    #[async_recursion]
-    async fn exec(
-        &self,
-        res: Option<ExecResult>,
-        node: Option<Arc<Node>>,
-        cur: usize,
-    ) -> Result<Option<ExecResult>, ExecError> {
+impl ExecutorImpl<'_> {
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, res)))]
+    fn exec(&self, res: ExecResult, node: Option<Arc<Node>>, cur: usize) -> Result<Option<ExecResult>, ExecError> {
         let node = match node {
-            None => match res {
-                None => return Ok(res),
-                Some(mut res) => {
-                    res.end = cur - 1;
+            None => {
+                let mut res = res;
+                res.end = cur;
 
-                    return Ok(Some(res));
-                }
-            },
+                // The whole match is always group 0.
+                res.groups.insert(0, (res.start, res.end));
+                res.groups.sort_keys();
+
+                return Ok(Some(res));
+            }
             Some(node) => node,
         };
 
         match &node.val {
-            NodeVal::Poisoned => Err(ExecError::PoisonedNode),
-            NodeVal::Any => {
-                if cur == self.n {
-                    return Ok(None);
-                }
-
-                self.exec(res.or(Some(ExecResult::new(cur))), node.next.clone(), cur + 1)
-                    .await
-            }
+            NodeVal::Any => match self.unit_at(cur) {
+                Some((Some(_), len)) => self.exec(res, node.next.clone(), cur + len),
+                Some((None, len)) if self.match_invalid_utf8 => self.exec(res, node.next.clone(), cur + len),
+                _ => Ok(None),
+            },
             NodeVal::Start => {
                 if cur == 0 {
-                    self.exec(res.or(Some(ExecResult::new(cur))), node.next.clone(), cur)
-                        .await
+                    self.exec(res, node.next.clone(), cur)
                 } else {
                     Ok(None)
                 }
             }
             NodeVal::End => {
                 if cur == self.n {
-                    return self
-                        .exec(res.or(Some(ExecResult::new(cur))), node.next.clone(), cur)
-                        .await;
+                    return self.exec(res, node.next.clone(), cur);
                 }
 
                 Ok(None)
             }
             NodeVal::Word(word) => {
-                if cur >= self.n {
+                if !self.input[cur..].starts_with(word.as_bytes()) {
+                    debug!("no match!");
                     return Ok(None);
                 }
 
-                match self.find_word(word, cur, res.is_none()) {
-                    None => {
-                        debug!("no match!");
-                        Ok(None)
-                    }
-                    Some((start, end)) => {
-                        debug!("matched!");
-                        self.exec(res.or(Some(ExecResult::new(start))), node.next.clone(), end + 1)
-                            .await
-                    }
-                }
+                debug!("matched!");
+                self.exec(res, node.next.clone(), cur + word.len())
             }
             NodeVal::Optional(to_test) => {
-                // Branch the expression into two versions: one that has this node and one that doesn't and add both to the frontier.
+                // Branch the expression into two versions: one that has this node and one that doesn't.
 
                 // Branch the "skip this node" case.
-                self.frontier.lock().await.push_front(ExecutorState {
+                self.frontier.borrow_mut().push_front(ExecutorState {
                     res: res.clone(),
                     node: node.next.clone(),
                     cur,
                 });
-                debug!("state: {:?}", self.frontier.lock().await.front());
+                debug!("state: {:?}", self.frontier.borrow().front());
 
-                // Branch the "take this node" case.
+                // Try the "take this node" case.
                 self.exec(res, Some(to_test.with_tail_option(node.next.clone())), cur)
-                    .await
             }
             NodeVal::ZeroOrMore { node: to_test, greedy } => {
-                self.match_zero_or_more(res, node.clone(), cur, to_test.clone(), *greedy)
-                    .await
+                self.match_zero_or_more(res, node.clone(), cur, to_test, *greedy)
             }
             NodeVal::OneOrMore { node: to_test, greedy } => {
-                self.match_one_or_more(res, node.clone(), cur, to_test.clone(), *greedy)
-                    .await
+                // Match once, then loop back into a zero-or-more match.
+                let repeat = Node {
+                    val: NodeVal::ZeroOrMore {
+                        node: to_test.clone(),
+                        greedy: *greedy,
+                    },
+                    next: node.next.clone(),
+                    span: node.span,
+                }
+                .arc();
+
+                self.exec(res, Some(Self::iteration(to_test, repeat, cur)), cur)
             }
             NodeVal::RepetitionRange {
                 node: to_test,
                 min,
                 max,
-            } => {
-                let mut res = res;
-                let mut cur = cur;
-
-                // Match min times.
-                for i in 0..*min {
-                    if i == *min {
-                        break;
-                    }
-
-                    let new_res = self.exec(res.clone(), Some(to_test.clone()), cur).await?;
-                    res = ExecResult::map_options(res, new_res.clone());
-                    match new_res {
-                        None => return Ok(None),
-                        Some(new_res) => {
-                            cur = new_res.end + 1;
-                        }
+            } => match (*min, *max) {
+                // We've matched as many times as we're allowed to; move on!
+                (_, Some(0)) => self.exec(res, node.next.clone(), cur),
+                // We don't have an upper limit; try matching zero-or-more times.
+                (0, None) => self.match_zero_or_more(res, node.clone(), cur, to_test, true),
+                (min, max) => {
+                    let repeat = Node {
+                        val: NodeVal::RepetitionRange {
+                            min: min.saturating_sub(1),
+                            max: max.map(|max| max - 1),
+                            node: to_test.clone(),
+                        },
+                        next: node.next.clone(),
+                        span: node.span,
                     }
-                }
-
-                match max {
-                    Some(max) => {
-                        // If min == max, we're done; move on!
-                        if *min == *max {
-                            return self.exec(res, node.next.clone(), cur).await;
-                        }
+                    .arc();
 
-                        // Branch two states: one where we don't match again, and one where we match {1, max-min}
-
-                        // Push the "match again {1,max-min}" state.
-                        self.frontier.lock().await.push_front(ExecutorState {
+                    // Once the minimum is met, branch the "don't match again" case.
+                    if min == 0 {
+                        self.frontier.borrow_mut().push_front(ExecutorState {
                             res: res.clone(),
-                            node: Some(
-                                Node {
-                                    val: NodeVal::RepetitionRange {
-                                        min: 1,
-                                        max: Some(*max - *min),
-                                        node: to_test.clone(),
-                                    },
-                                    next: node.next.clone(),
-                                }
-                                .arc(),
-                            ),
+                            node: node.next.clone(),
                             cur,
                         });
-
-                        // Try the "don't match" state.
-                        self.exec(res, node.next.clone(), cur).await
-                    }
-                    None => {
-                        // We don't have an upper limit; try matching zero-or-more times.
-                        self.match_zero_or_more(res, node.clone(), cur, to_test.clone(), false)
-                            .await
                     }
+
+                    self.exec(res, Some(Self::iteration(to_test, repeat, cur)), cur)
                 }
-            }
+            },
             NodeVal::Group { group, cfg: group_cfg } => {
                 // Take the inner group and append a GroupEnd val that will mark the end of the group when we hit it
                 // (which means we don't have to deal with nested states, especially when exploring different expression branches in the frontier).
@@ -319,144 +522,142 @@ impl ExecutorImpl {
                             cfg: group_cfg.clone(),
                         },
                         next: node.next.clone(),
+                        span: node.span,
                     }
                     .arc(),
                 );
 
-                self.exec(res, Some(new_head), cur).await
+                self.exec(res, Some(new_head), cur)
             }
             NodeVal::GroupEnd { start, cfg } => {
-                // Record this group if needed.
                 let mut res = res;
-                if let (Some(res), Some(group_cfg)) = (&mut res, cfg) {
-                    match group_cfg {
-                        parser::GroupConfig::NonCapturing => {}
-                        parser::GroupConfig::Named(name) => {
-                            res.groups.insert(name.clone(), (*start, cur - 1));
-                        }
+                if let Some(parser::GroupConfig::Capturing(capture)) = cfg {
+                    res.groups.insert(capture.index, (*start, cur));
+                    if let Some(name) = &capture.name {
+                        res.names.insert(name.clone(), capture.index);
                     }
                 }
 
-                self.exec(res, node.next.clone(), cur).await
+                self.exec(res, node.next.clone(), cur)
             }
+            NodeVal::IterationEnd { start } => match &node.next {
+                // An iteration that matched empty would repeat forever without getting anywhere, so stop repeating once
+                // the minimum is met (the iterations still required are finite and may match something).
+                Some(repeat)
+                    if *start == cur && !matches!(repeat.val, NodeVal::RepetitionRange { min, .. } if min > 0) =>
+                {
+                    debug!("matched empty!");
+                    self.exec(res, repeat.next.clone(), cur)
+                }
+                next => self.exec(res, next.clone(), cur),
+            },
             NodeVal::Set { set, inverted } => {
-                let ch = match self.input.chars().nth(cur) {
+                let (ch, len) = match self.unit_at(cur) {
                     None => return Ok(None),
-                    Some(ch) => ch,
+                    Some(unit) => unit,
                 };
 
-                match (inverted, set.contains(&ch)) {
+                match (inverted, ch.map(|ch| set.contains(&ch))) {
                     // not inverted, did find:
-                    (false, true) | (true, false) => {
-                        self.exec(res.or(Some(ExecResult::new(cur))), node.next.clone(), cur + 1)
-                            .await
-                    }
+                    (false, Some(true)) | (true, Some(false)) => self.exec(res, node.next.clone(), cur + len),
+                    // A byte that isn't part of a char isn't in any set.
+                    (true, None) if self.match_invalid_utf8 => self.exec(res, node.next.clone(), cur + len),
                     _ => Ok(None),
                 }
             }
-            NodeVal::Or { left, right } => {
-                // Emit two states: one where we take the left and one where we take the right.
-                // NOTE: this might require more plumbing because we might want to allow either as a valid match; not sure what the actual spec is here.
+            NodeVal::Backreference(index) => match self.match_backreference(&res, *index, cur) {
+                None => Ok(None),
+                Some(cur) => self.exec(res, node.next.clone(), cur),
+            },
+            NodeVal::Byte(byte) => {
+                if self.raw_bytes {
+                    return match self.input.get(cur) {
+                        Some(found) if found == byte => self.exec(res, node.next.clone(), cur + 1),
+                        _ => Ok(None),
+                    };
+                }
 
-                // Push the right state.
-                self.frontier.lock().await.push_front(ExecutorState {
-                    res: res.clone(),
-                    node: Some(right.with_tail_option(node.next.clone())),
-                    cur,
-                });
+                match self.unit_at(cur) {
+                    Some((Some(ch), len)) if ch == char::from(*byte) => self.exec(res, node.next.clone(), cur + len),
+                    _ => Ok(None),
+                }
+            }
+            NodeVal::Or(branches) => {
+                // Emit a state per branch; the first branch is tried right away and the rest are queued in order.
+                let mut frontier = self.frontier.borrow_mut();
+                for branch in branches[1..].iter().rev() {
+                    frontier.push_front(ExecutorState {
+                        res: res.clone(),
+                        node: Some(branch.with_tail_option(node.next.clone())),
+                        cur,
+                    });
+                }
+                drop(frontier);
 
-                // Try the left state.
-                self.exec(res, Some(left.with_tail_option(node.next.clone())), cur)
-                    .await
+                self.exec(res, Some(branches[0].with_tail_option(node.next.clone())), cur)
             }
         }
     }
 
-    fn find_word(&self, word: &str, start: usize, can_move_window: bool) -> Option<(usize, usize)> {
-        let word_n = word.len();
-
-        let mut cur = start;
-
-        loop {
-            if cur + word_n > self.n {
-                return None;
+    /// Returns a copy of `to_test` that continues with `repeat` once it has matched (starting at `cur`).
+    fn iteration(to_test: &Arc<Node>, repeat: Arc<Node>, cur: usize) -> Arc<Node> {
+        to_test.with_tail(
+            Node {
+                val: NodeVal::IterationEnd { start: cur },
+                span: repeat.span,
+                next: Some(repeat),
             }
+            .arc(),
+        )
+    }
 
-            let substr = &self.input[cur..cur + word_n];
-            if word == substr {
-                return Some((cur, cur + word_n - 1));
-            }
+    /// Decodes the char that starts at byte offset `cur` along with its length, or `None` for the char if the byte
+    /// there isn't the start of valid UTF-8 (which can only happen when matching bytes).
+    fn unit_at(&self, cur: usize) -> Option<(Option<char>, usize)> {
+        if cur >= self.n {
+            return None;
+        }
 
-            if !can_move_window {
-                return None;
-            }
+        Some(match haystack::decode_char(&self.input[cur..]) {
+            Some(ch) => (Some(ch), ch.len_utf8()),
+            None => (None, 1),
+        })
+    }
 
-            cur += 1;
+    fn match_backreference(&self, res: &ExecResult, index: usize, cur: usize) -> Option<usize> {
+        // A group that hasn't participated in the match can't be referenced.
+        let (start, end) = res.group(index)?;
+        let captured = &self.input[start..end];
+
+        if self.input[cur..].starts_with(captured) {
+            Some(cur + captured.len())
+        } else {
+            None
         }
     }
 
-    #[async_recursion]
-    async fn match_zero_or_more(
+    fn match_zero_or_more(
         &self,
-        res: Option<ExecResult>,
+        res: ExecResult,
         node: Arc<Node>,
         cur: usize,
-        to_test: Arc<Node>,
+        to_test: &Arc<Node>,
         greedy: bool,
     ) -> Result<Option<ExecResult>, ExecError> {
-        // Branch the expression into two versions: one that matches one ore more times and one that doesn't contain the node and add both to the frontier.
+        // Branch the expression into two versions: one that matches again (and then loops back to this node) and one
+        // that moves on; the one we prefer is tried right away and the other is queued.
+        let next = node.next.clone();
+        let again = Some(Self::iteration(to_test, node, cur));
+
+        let (preferred, fallback) = if greedy { (again, next) } else { (next, again) };
 
-        // Branch the "skip this node" case.
-        self.frontier.lock().await.push_front(ExecutorState {
+        self.frontier.borrow_mut().push_front(ExecutorState {
             res: res.clone(),
-            node: node.next.clone(),
+            node: fallback,
             cur,
         });
-        debug!("state: {:?}", self.frontier.lock().await.front());
-
-        // Branch the "one-or-more" case.
-        self.match_one_or_more(res, node, cur, to_test, greedy).await
-    }
-
-    async fn match_one_or_more(
-        &self,
-        res: Option<ExecResult>,
-        node: Arc<Node>,
-        cur: usize,
-        to_test: Arc<Node>,
-        greedy: bool,
-    ) -> Result<Option<ExecResult>, ExecError> {
-        let mut res = res;
-        let mut cur = cur;
-
-        debug!("looking for 1 or more matches; cur: {:?}", cur);
-
-        // Match at least once.
-        let new_res = self.exec(res.clone(), Some(to_test.clone()), cur).await?;
-        res = ExecResult::map_options(res, new_res.clone());
-        match new_res {
-            None => {
-                debug!("failed to match!");
-                return Ok(None);
-            }
-            Some(new_res) => {
-                cur = new_res.end + 1;
-
-                debug!("matched! cur: {:?}", cur);
-            }
-        }
-
-        // If lazy and we can match the next node, we're done!
-        if !greedy {
-            debug!("lazy matching...");
-            let res = self.exec(res.clone(), node.next.clone(), cur).await?;
-            if res.is_some() {
-                debug!("lazy matched!");
-                return Ok(res);
-            }
-        }
+        debug!("state: {:?}", self.frontier.borrow().front());
 
-        // We've branched our minimum number, so now we need to either keep matching or give up; we can model that with a "zero-or-more" match!
-        self.match_zero_or_more(res, node.clone(), cur, to_test, greedy).await
+        self.exec(res, preferred, cur)
     }
 }
@@ -1,6 +1,6 @@
 use std::{cell::RefCell, rc::Rc, sync::Arc};
 
-use crate::parser::{Node, NodeVal};
+use crate::parser::{Node, NodeVal, Span};
 
 pub trait AppendNode: Sized {
     fn with_tail(&self, tail: Self) -> Self;
@@ -36,6 +36,7 @@ impl IntoMutNode for Node {
 pub struct MutNode {
     pub val: NodeVal,
     pub next: Option<Rc<RefCell<MutNode>>>,
+    pub span: Span,
 }
 
 impl MutNode {
@@ -83,6 +84,7 @@ impl From<Node> for MutNode {
                 Rc::new(RefCell::new(MutNode {
                     val: node.val.clone(),
                     next: rec(&node.next),
+                    span: node.span,
                 }))
             })
         }
@@ -90,6 +92,7 @@ impl From<Node> for MutNode {
         MutNode {
             val: node.val.clone(),
             next: rec(&node.next),
+            span: node.span,
         }
     }
 }
@@ -108,6 +111,7 @@ impl TryFrom<MutNode> for Node {
                 None => None,
                 Some(node) => Some(Arc::new(try_unwrap_mut_node(node)?.try_into()?)),
             },
+            span: node.span,
         })
     }
 }
@@ -0,0 +1,327 @@
+use core::fmt;
+use std::sync::Arc;
+
+use crate::parser::{Capture, GroupConfig, Node, NodeVal, ParseResult};
+
+/// A regex dialect that parse results can be exported to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flavor {
+    /// Perl-compatible regular expressions (PCRE2, and tools built on it).
+    Pcre,
+
+    /// JavaScript regular expressions, written so they can be used as a `/.../u` literal.
+    EcmaScript,
+
+    /// POSIX extended regular expressions (`grep -E`, PostgreSQL's `~` operator).
+    PosixEre,
+}
+
+pub enum ExportError {
+    /// The pattern uses a construct that has no equivalent in the target flavor.
+    Unsupported { flavor: Flavor, construct: &'static str },
+}
+
+impl ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unsupported { flavor, construct } => write!(f, "{} cannot be expressed in {:?}", construct, flavor),
+        }
+    }
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt(f)
+    }
+}
+
+impl fmt::Debug for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt(f)
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+/// Translates a parse result into an equivalent pattern in another regex flavor.
+///
+/// The exported pattern has the same semantics as rustex: `.` matches newlines and `^`/`$` only match at the very
+/// start/end of the input. The one exception is a backreference to a group that didn't participate in the match: it
+/// never matches in rustex (or PCRE), but matches the empty string in ECMAScript, so `(?<g>a)?(?P=g)b` matches `b` only
+/// once exported to ECMAScript.
+pub fn export(parsed: &ParseResult, flavor: Flavor) -> Result<String, ExportError> {
+    let mut exporter = Exporter {
+        flavor,
+        captures: parsed.captures(),
+        out: String::new(),
+    };
+
+    if let Some(head) = &parsed.head {
+        exporter.export_chain(head)?;
+    }
+
+    Ok(exporter.out)
+}
+
+struct Exporter {
+    flavor: Flavor,
+    captures: Vec<Capture>,
+    out: String,
+}
+
+impl Exporter {
+    const SPECIAL_CHARS: &'static [char] = &['\\', '^', '$', '.', '|', '?', '*', '+', '(', ')', '[', ']', '{', '}'];
+
+    fn unsupported<T>(&self, construct: &'static str) -> Result<T, ExportError> {
+        Err(ExportError::Unsupported {
+            flavor: self.flavor,
+            construct,
+        })
+    }
+
+    fn capture_name(&self, index: usize) -> Option<&str> {
+        self.captures
+            .iter()
+            .find(|capture| capture.index == index)
+            .and_then(|capture| capture.name.as_deref())
+    }
+
+    fn export_chain(&mut self, head: &Node) -> Result<(), ExportError> {
+        // An alternation that shares its chain with other nodes has to be wrapped so it doesn't swallow them.
+        let is_single = head.next.is_none();
+
+        for node in head.iter() {
+            match &node.val {
+                NodeVal::Or(_) if !is_single => self.export_wrapped(node)?,
+                _ => self.export_node(node)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Exports a single node (ignoring `next`) as a non-capturing group.
+    fn export_wrapped(&mut self, node: &Node) -> Result<(), ExportError> {
+        if self.flavor == Flavor::PosixEre {
+            return self.unsupported("a non-capturing group");
+        }
+
+        self.out.push_str("(?:");
+        self.export_node(node)?;
+        self.out.push(')');
+
+        Ok(())
+    }
+
+    /// Exports a node (ignoring `next`) so that a quantifier can be applied to it.
+    fn export_atom(&mut self, node: &Arc<Node>) -> Result<(), ExportError> {
+        let is_atom = node.next.is_none()
+            && match &node.val {
+                NodeVal::Word(word) => word.chars().count() == 1,
+                NodeVal::Any
+                | NodeVal::Group { .. }
+                | NodeVal::Set { .. }
+                | NodeVal::Backreference(_)
+                | NodeVal::Byte(_) => true,
+                _ => false,
+            };
+
+        if is_atom {
+            return self.export_node(node);
+        }
+
+        if self.flavor == Flavor::PosixEre {
+            return self.unsupported("a non-capturing group");
+        }
+
+        self.out.push_str("(?:");
+        self.export_chain(node)?;
+        self.out.push(')');
+
+        Ok(())
+    }
+
+    fn export_node(&mut self, node: &Node) -> Result<(), ExportError> {
+        match &node.val {
+            NodeVal::GroupEnd { .. } | NodeVal::IterationEnd { .. } => return self.unsupported("an internal node"),
+            NodeVal::Word(word) => {
+                for ch in word.chars() {
+                    self.push_literal(ch);
+                }
+            }
+            NodeVal::Any => self.out.push_str(match self.flavor {
+                Flavor::Pcre => "(?s:.)",
+                Flavor::EcmaScript => "[\\s\\S]",
+                Flavor::PosixEre => ".",
+            }),
+            NodeVal::Start => self.out.push('^'),
+            NodeVal::End => self.out.push_str(match self.flavor {
+                // PCRE's '$' also matches before a trailing newline.
+                Flavor::Pcre => "\\z",
+                Flavor::EcmaScript | Flavor::PosixEre => "$",
+            }),
+            NodeVal::ZeroOrMore { node, greedy } => {
+                self.export_atom(node)?;
+                self.out.push('*');
+                self.push_lazy(*greedy)?;
+            }
+            NodeVal::OneOrMore { node, greedy } => {
+                self.export_atom(node)?;
+                self.out.push('+');
+                self.push_lazy(*greedy)?;
+            }
+            NodeVal::Optional(node) => {
+                self.export_atom(node)?;
+                self.out.push('?');
+            }
+            NodeVal::RepetitionRange { min, max, node } => {
+                self.export_atom(node)?;
+
+                match max {
+                    None => self.out.push_str(&format!("{{{},}}", min)),
+                    Some(max) if max == min => self.out.push_str(&format!("{{{}}}", min)),
+                    Some(max) => self.out.push_str(&format!("{{{},{}}}", min, max)),
+                }
+            }
+            NodeVal::Group { group, cfg } => {
+                match cfg {
+                    None => self.out.push('('),
+                    Some(GroupConfig::NonCapturing) => {
+                        if self.flavor == Flavor::PosixEre {
+                            return self.unsupported("a non-capturing group");
+                        }
+
+                        self.out.push_str("(?:");
+                    }
+                    Some(GroupConfig::Capturing(capture)) => match &capture.name {
+                        None => self.out.push('('),
+                        Some(name) => {
+                            if self.flavor == Flavor::PosixEre {
+                                return self.unsupported("a named group");
+                            }
+
+                            self.out.push_str(&format!("(?<{}>", name));
+                        }
+                    },
+                }
+
+                self.export_chain(group)?;
+                self.out.push(')');
+            }
+            NodeVal::Set { set, inverted } => self.push_set(set, *inverted)?,
+            NodeVal::Or(branches) => {
+                for (i, branch) in branches.iter().enumerate() {
+                    if i > 0 {
+                        self.out.push('|');
+                    }
+
+                    self.export_chain(branch)?;
+                }
+            }
+            NodeVal::Backreference(index) => match self.flavor {
+                Flavor::Pcre => self.out.push_str(&format!("\\g{{{}}}", index)),
+                // A bare `\N` followed by a digit would be read as a different reference (or an octal escape).
+                Flavor::EcmaScript => match self.capture_name(*index) {
+                    Some(name) => self.out.push_str(&format!("\\k<{}>", name)),
+                    None => self.out.push_str(&format!("(?:\\{})", index)),
+                },
+                Flavor::PosixEre => return self.unsupported("a backreference"),
+            },
+            NodeVal::Byte(byte) => match self.flavor {
+                Flavor::Pcre | Flavor::EcmaScript => self.out.push_str(&format!("\\x{:02X}", byte)),
+                Flavor::PosixEre => self.push_literal(char::from(*byte)),
+            },
+        };
+
+        Ok(())
+    }
+
+    fn push_lazy(&mut self, greedy: bool) -> Result<(), ExportError> {
+        if greedy {
+            return Ok(());
+        }
+
+        if self.flavor == Flavor::PosixEre {
+            return self.unsupported("a lazy quantifier");
+        }
+
+        self.out.push('?');
+
+        Ok(())
+    }
+
+    fn push_literal(&mut self, ch: char) {
+        // '/' is escaped for ECMAScript so the pattern can be dropped into a regex literal.
+        if Self::SPECIAL_CHARS.contains(&ch) || (self.flavor == Flavor::EcmaScript && ch == '/') {
+            self.out.push('\\');
+        }
+
+        self.out.push(ch);
+    }
+
+    fn push_set(&mut self, set: &indexmap::IndexSet<char>, inverted: bool) -> Result<(), ExportError> {
+        if set.is_empty() {
+            let empty_set = match (self.flavor, inverted) {
+                (Flavor::Pcre, false) => "(?!)",
+                (Flavor::Pcre, true) => "(?s:.)",
+                (Flavor::EcmaScript, false) => "[]",
+                (Flavor::EcmaScript, true) => "[^]",
+                (Flavor::PosixEre, _) => return self.unsupported("an empty set"),
+            };
+
+            self.out.push_str(empty_set);
+            return Ok(());
+        }
+
+        // POSIX bracket expressions have no escapes, so a set that's just '^' has to be written as a literal.
+        if self.flavor == Flavor::PosixEre && !inverted && set.len() == 1 && set.contains(&'^') {
+            self.out.push_str("\\^");
+            return Ok(());
+        }
+
+        self.out.push('[');
+        if inverted {
+            self.out.push('^');
+        }
+
+        match self.flavor {
+            Flavor::Pcre | Flavor::EcmaScript => {
+                for ch in set {
+                    if matches!(ch, '\\' | ']' | '[' | '^' | '-') {
+                        self.out.push('\\');
+                    }
+
+                    self.out.push(*ch);
+                }
+            }
+
+            // Special members are positioned instead: ']' goes first, '^' anywhere but first and '-' last.
+            Flavor::PosixEre => {
+                let rest = set
+                    .iter()
+                    .filter(|ch| !matches!(ch, ']' | '^' | '-'))
+                    .collect::<String>();
+                let (has_bracket, has_caret, has_dash) = (set.contains(&']'), set.contains(&'^'), set.contains(&'-'));
+
+                if has_bracket {
+                    self.out.push(']');
+                }
+
+                self.out.push_str(&rest);
+
+                match (has_caret, has_dash) {
+                    // A '-' is also literal when it comes first, which keeps the '^' from being first.
+                    (true, true) if !inverted && !has_bracket && rest.is_empty() => self.out.push_str("-^"),
+                    (true, true) => self.out.push_str("^-"),
+                    (true, false) => self.out.push('^'),
+                    (false, true) => self.out.push('-'),
+                    (false, false) => {}
+                }
+            }
+        }
+
+        self.out.push(']');
+
+        Ok(())
+    }
+}
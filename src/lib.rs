@@ -1,5 +1,13 @@
 extern crate maplit;
 
+pub mod analysis;
 pub mod executor;
+pub mod export;
+pub mod optimizer;
 pub mod parser;
-pub mod replace;
\ No newline at end of file
+pub mod regex;
+pub mod replace;
+
+pub use regex::{Captures, Match, Regex, RegexBuilder};
+
+mod trace;
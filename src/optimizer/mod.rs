@@ -0,0 +1,316 @@
+use std::sync::Arc;
+
+use indexmap::IndexSet;
+
+use crate::parser::{GroupConfig, Node, NodeVal, ParseResult, Span};
+
+/// Rewrites a parse result into a smaller graph that matches the same inputs with the same captures.
+///
+/// Adjacent words are merged, single-member sets become literals, alternations are flattened, adjacent
+/// single-char alternatives become sets and adjacent literal alternatives that share a prefix are factored into a
+/// trie (`abc|abd` becomes `ab(?:c|d)`). Alternatives are never reordered.
+pub fn optimize(parsed: &ParseResult) -> ParseResult {
+    let mut optimized = parsed.clone();
+    optimized.head = parsed.head.as_ref().map(|head| optimize_chain(head));
+
+    optimized
+}
+
+fn optimize_chain(head: &Node) -> Arc<Node> {
+    let mut nodes = vec![];
+    for node in head.iter() {
+        nodes.extend(optimize_node(node));
+    }
+
+    link(merge_words(nodes)).expect("optimizing a chain should never remove all of its nodes")
+}
+
+/// Optimizes a single node (ignoring `next`), which might expand into a chain of nodes.
+fn optimize_node(node: &Node) -> Vec<Node> {
+    let val = match &node.val {
+        NodeVal::Set { set, inverted: false } if set.len() == 1 => {
+            NodeVal::Word(set.first().expect("already checked the length").to_string())
+        }
+        NodeVal::Or(branches) => return optimize_alternation(branches, node.span),
+        NodeVal::ZeroOrMore { node, greedy } => NodeVal::ZeroOrMore {
+            node: optimize_chain(node),
+            greedy: *greedy,
+        },
+        NodeVal::OneOrMore { node, greedy } => NodeVal::OneOrMore {
+            node: optimize_chain(node),
+            greedy: *greedy,
+        },
+        NodeVal::Optional(node) => NodeVal::Optional(optimize_chain(node)),
+        NodeVal::RepetitionRange { min, max, node } => NodeVal::RepetitionRange {
+            min: *min,
+            max: *max,
+            node: optimize_chain(node),
+        },
+        NodeVal::Group { group, cfg } => NodeVal::Group {
+            group: optimize_chain(group),
+            cfg: cfg.clone(),
+        },
+        val => val.clone(),
+    };
+
+    vec![Node {
+        val,
+        next: None,
+        span: node.span,
+    }]
+}
+
+fn optimize_alternation(branches: &[Arc<Node>], span: Span) -> Vec<Node> {
+    let branches = branches
+        .iter()
+        .map(|branch| {
+            let mut nodes = vec![];
+            for node in branch.iter() {
+                nodes.extend(optimize_node(node));
+            }
+
+            merge_words(nodes)
+        })
+        .collect::<Vec<_>>();
+
+    let mut branches = factor_prefixes(merge_char_branches(branches));
+
+    // Everything might have been merged into a single alternative, which doesn't need an alternation at all.
+    if branches.len() == 1 {
+        return branches.pop().unwrap();
+    }
+
+    vec![Node {
+        val: NodeVal::Or(branches.into_iter().map(|branch| link(branch).unwrap()).collect()),
+        next: None,
+        span,
+    }]
+}
+
+/// Merges runs of adjacent alternatives that each match a single char into one set.
+fn merge_char_branches(branches: Vec<Vec<Node>>) -> Vec<Vec<Node>> {
+    let mut res: Vec<Vec<Node>> = vec![];
+
+    for branch in branches {
+        let chars = match branch_chars(&branch) {
+            None => {
+                res.push(branch);
+                continue;
+            }
+            Some(chars) => chars,
+        };
+
+        match res.last_mut().map(|last| (branch_chars(last), last)) {
+            Some((Some(mut last_chars), last)) => {
+                last_chars.extend(chars);
+
+                let span = Span {
+                    start: last[0].span.start,
+                    end: branch[0].span.end,
+                };
+                *last = vec![Node {
+                    val: NodeVal::Set {
+                        set: last_chars,
+                        inverted: false,
+                    },
+                    next: None,
+                    span,
+                }];
+            }
+            _ => res.push(branch),
+        }
+    }
+
+    res
+}
+
+/// Returns the chars an alternative matches if it always matches exactly one of them.
+fn branch_chars(branch: &[Node]) -> Option<IndexSet<char>> {
+    match branch {
+        [node] => match &node.val {
+            NodeVal::Word(word) if word.chars().count() == 1 => Some(word.chars().collect()),
+            NodeVal::Set { set, inverted: false } => Some(set.clone()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Factors the common prefix out of runs of adjacent literal alternatives.
+fn factor_prefixes(branches: Vec<Vec<Node>>) -> Vec<Vec<Node>> {
+    let mut res = vec![];
+
+    let mut branches = branches.into_iter().peekable();
+    while let Some(branch) = branches.next() {
+        let first_ch = match branch_literal(&branch).and_then(|word| word.chars().next()) {
+            None => {
+                res.push(branch);
+                continue;
+            }
+            Some(ch) => ch,
+        };
+
+        let mut run = vec![branch];
+        while let Some(next) = branches.peek() {
+            match branch_literal(next) {
+                Some(word) if word.starts_with(first_ch) => run.push(branches.next().unwrap()),
+                _ => break,
+            }
+        }
+
+        match factor_run(&run) {
+            None => res.extend(run),
+            Some(factored) => res.push(factored),
+        }
+    }
+
+    res
+}
+
+/// Factors `abc|abd` into `ab(?:c|d)`, or `abc|ab` into `ab(?:c)?`.
+fn factor_run(run: &[Vec<Node>]) -> Option<Vec<Node>> {
+    if run.len() < 2 {
+        return None;
+    }
+
+    let words = run
+        .iter()
+        .map(|branch| branch_literal(branch).expect("runs only contain literals"))
+        .collect::<Vec<_>>();
+
+    let prefix_len = words
+        .iter()
+        .map(|word| word.chars().zip(words[0].chars()).take_while(|(a, b)| a == b).count())
+        .min()
+        .expect("already checked the run isn't empty");
+
+    let suffixes = words
+        .iter()
+        .zip(run)
+        .map(|(word, branch)| {
+            let suffix = word.chars().skip(prefix_len).collect::<String>();
+            let span = branch[0].span;
+
+            (suffix, span)
+        })
+        .collect::<Vec<_>>();
+
+    // An empty alternative can only be expressed by making the rest optional, which only preserves the order of the
+    // alternatives when it comes last.
+    let (last_suffix, _) = suffixes.last().expect("already checked the run isn't empty");
+    let is_optional = last_suffix.is_empty();
+    if suffixes[..suffixes.len() - 1]
+        .iter()
+        .any(|(suffix, _)| suffix.is_empty())
+    {
+        return None;
+    }
+
+    let span = Span {
+        start: run[0][0].span.start,
+        end: run.last().expect("already checked the run isn't empty")[0].span.end,
+    };
+
+    let prefix_end = span.start + prefix_len;
+    let prefix = Node {
+        val: NodeVal::Word(words[0].chars().take(prefix_len).collect()),
+        next: None,
+        span: Span {
+            start: span.start,
+            end: prefix_end,
+        },
+    };
+
+    // Build the remaining alternatives as an alternation of their own so they get optimized too.
+    let mut rest = suffixes
+        .into_iter()
+        .filter(|(suffix, _)| !suffix.is_empty())
+        .map(|(suffix, span)| Node {
+            val: NodeVal::Word(suffix),
+            next: None,
+            span,
+        })
+        .collect::<Vec<_>>();
+
+    let rest_head = match rest.len() {
+        1 => Arc::new(rest.pop().unwrap()),
+        _ => optimize_chain(&Node {
+            val: NodeVal::Or(rest.into_iter().map(Arc::new).collect()),
+            next: None,
+            span,
+        }),
+    };
+
+    let wrap = |group| Node {
+        val: NodeVal::Group {
+            group,
+            cfg: Some(GroupConfig::NonCapturing),
+        },
+        next: None,
+        span,
+    };
+
+    let mut res = vec![prefix];
+    match (is_optional, &rest_head.val) {
+        (false, NodeVal::Or(_)) => res.push(wrap(rest_head)),
+        (false, _) => res.extend(rest_head.iter().map(|node| Node {
+            next: None,
+            ..node.clone()
+        })),
+        (true, NodeVal::Or(_)) => res.push(optional(wrap(rest_head), span)),
+        (true, _) if rest_head.next.is_some() => res.push(optional(wrap(rest_head), span)),
+        (true, _) => res.push(optional(Arc::unwrap_or_clone(rest_head), span)),
+    }
+
+    Some(merge_words(res))
+}
+
+fn optional(node: Node, span: Span) -> Node {
+    Node {
+        val: NodeVal::Optional(Arc::new(node)),
+        next: None,
+        span,
+    }
+}
+
+/// Returns the word an alternative matches if it's just a literal.
+fn branch_literal(branch: &[Node]) -> Option<&str> {
+    match branch {
+        [node] => match &node.val {
+            NodeVal::Word(word) => Some(word),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Merges runs of adjacent words into a single word.
+fn merge_words(nodes: Vec<Node>) -> Vec<Node> {
+    let mut res: Vec<Node> = vec![];
+
+    for node in nodes {
+        if let (Some(last), NodeVal::Word(word)) = (res.last_mut(), &node.val) {
+            if let NodeVal::Word(last_word) = &mut last.val {
+                last_word.push_str(word);
+                last.span.end = node.span.end;
+
+                continue;
+            }
+        }
+
+        res.push(node);
+    }
+
+    res
+}
+
+/// Links a list of nodes into a chain.
+fn link(nodes: Vec<Node>) -> Option<Arc<Node>> {
+    let mut head = None;
+    for mut node in nodes.into_iter().rev() {
+        node.next = head;
+        head = Some(Arc::new(node));
+    }
+
+    head
+}
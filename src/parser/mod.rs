@@ -1,35 +1,64 @@
 use core::fmt;
-use std::{cell::RefCell, iter::Peekable, mem, sync::Arc};
+use std::{collections::HashMap, io, iter::Peekable, mem, sync::Arc};
 
 mod node;
 mod parse_node;
+mod reader;
+#[cfg(feature = "serde")]
+mod serialize;
+mod syntax;
+mod validate;
 
 pub use node::*;
 use parse_node::*;
-
-fn rcref<T>(val: T) -> Arc<RefCell<T>> {
-    Arc::new(RefCell::new(val))
-}
+pub use syntax::Syntax;
+pub use validate::{ParseWarning, ParseWarningKind};
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum GroupConfig {
     NonCapturing,
-    Named(String),
+    Capturing(Capture),
+}
+
+/// A capturing group defined by a pattern.
+///
+/// Every capturing group is numbered, whether or not it's named; index 0 is reserved for the whole match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Capture {
+    /// The 1-based position of the group among all capturing groups, ordered by opening paren.
+    pub index: usize,
+
+    /// The group's name, if it was declared as `(?<name>...)`.
+    pub name: Option<String>,
 }
 
 pub enum ParseError {
     UnexpectedCharErr(char),
     UnterminatedCharSet,
-    EmptyCaptureGroup,
     MissingCharacterToEscape,
-    MissingRightSideOfOr,
     BadGroupConfig,
     MissingRepetitionRangeMin,
     UnexpectedRepetitionRangeCh(char),
     MissingLeftSideOfModifier,
-    UnexpectedEmptyParseNodeOption,
-    ParseGraphCycle,
     UnexpectedEndOfInput,
+    DuplicateGroupName(String),
+    InvalidGroupName(String),
+    UnknownGroupName(String),
+    BadSetRange(char, char),
+    UnknownCharClass(String),
+    NestingTooDeep(usize),
+    RepetitionTooLarge(u32),
+    TooManyNodes(usize),
+    BackreferenceToOpenGroup(String),
+    InvalidRepetitionRange { min: u32, max: u32 },
+    ByteInSet(u8),
+    SetRangeTooLarge(u32),
 }
 
 impl fmt::Debug for ParseError {
@@ -37,20 +66,28 @@ impl fmt::Debug for ParseError {
         match self {
             ParseError::UnexpectedCharErr(ch) => f.write_fmt(format_args!("unexpected char '{}'", ch)),
             ParseError::UnterminatedCharSet => f.write_str("unterminated character set"),
-            ParseError::EmptyCaptureGroup => f.write_str("empty capture group"),
             ParseError::MissingCharacterToEscape => f.write_str("missing character to escape"),
-            ParseError::MissingRightSideOfOr => f.write_str("missing right side of or"),
             ParseError::BadGroupConfig => f.write_str("bad group config"),
             ParseError::MissingRepetitionRangeMin => f.write_str("missing repetition range min"),
             ParseError::UnexpectedRepetitionRangeCh(ch) => {
                 f.write_fmt(format_args!("unexpected char '{}' in repetition range", ch))
             }
             ParseError::MissingLeftSideOfModifier => f.write_str("missing left side of modifier"),
-            ParseError::UnexpectedEmptyParseNodeOption => {
-                f.write_str("internal: unexpected unwrap of Option<Arc<RefCell<ParseNode>>>")
-            }
-            Self::ParseGraphCycle => write!(f, "found reference cycle in parse graph"),
             Self::UnexpectedEndOfInput => write!(f, "found unexpected end of input"),
+            Self::DuplicateGroupName(name) => write!(f, "duplicate group name '{}'", name),
+            Self::InvalidGroupName(name) => write!(f, "invalid group name '{}'", name),
+            Self::UnknownGroupName(name) => write!(f, "unknown group name '{}'", name),
+            Self::BadSetRange(start, end) => write!(f, "bad set range '{}-{}'", start, end),
+            Self::UnknownCharClass(class) => write!(f, "unknown character class '[:{}:]'", class),
+            Self::NestingTooDeep(max) => write!(f, "pattern nests deeper than the limit of {}", max),
+            Self::RepetitionTooLarge(max) => write!(f, "repetition count exceeds the limit of {}", max),
+            Self::TooManyNodes(max) => write!(f, "pattern has more nodes than the limit of {}", max),
+            Self::BackreferenceToOpenGroup(name) => write!(f, "backreference to group '{}' inside itself", name),
+            Self::InvalidRepetitionRange { min, max } => {
+                write!(f, "repetition range min {} is greater than max {}", min, max)
+            }
+            Self::SetRangeTooLarge(max) => write!(f, "set range covers more chars than the limit of {}", max),
+            Self::ByteInSet(byte) => write!(f, "byte '\\x{:02X}' can't be a set member", byte),
         }
     }
 }
@@ -67,13 +104,113 @@ impl<'str> fmt::Debug for ParseErrorWithContext<'str> {
         f.write_fmt(format_args!(" at :{}\n", self.cur))?;
         f.write_str(self.str)?;
         f.write_str("\n")?;
-        f.write_str(" ".repeat(self.cur - 1).as_str())?;
+        f.write_str(" ".repeat(self.cur.saturating_sub(1)).as_str())?;
         f.write_str("^")
     }
 }
 
+impl<'str> ParseErrorWithContext<'str> {
+    /// Drops the borrowed pattern so the error can outlive it.
+    pub fn into_owned(self) -> PositionedParseError {
+        PositionedParseError {
+            err: self.err,
+            position: self.cur,
+        }
+    }
+}
+
+/// A [`ParseError`] along with the number of pattern chars consumed when it happened.
+pub struct PositionedParseError {
+    pub err: ParseError,
+    pub position: usize,
+}
+
+impl PositionedParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} at :{}", self.err, self.position)
+    }
+}
+
+impl fmt::Display for PositionedParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt(f)
+    }
+}
+
+impl fmt::Debug for PositionedParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt(f)
+    }
+}
+
+impl std::error::Error for PositionedParseError {}
+
+/// An error from [`Parser::parse_reader`].
+pub enum ReadParseError {
+    /// The pattern couldn't be read or wasn't valid UTF-8.
+    Io(io::Error),
+    Parse(PositionedParseError),
+}
+
+impl ReadParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "failed to read pattern: {}", err),
+            Self::Parse(err) => err.fmt(f),
+        }
+    }
+}
+
+impl fmt::Display for ReadParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt(f)
+    }
+}
+
+impl fmt::Debug for ReadParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt(f)
+    }
+}
+
+impl std::error::Error for ReadParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Parse(err) => Some(err),
+        }
+    }
+}
+
+/// Parses patterns into [`ParseResult`]s.
+///
+/// Since patterns may come from untrusted users, the parser enforces limits on how deeply groups and alternations
+/// nest, how large repetition counts can be and how many nodes a pattern can compile to; exceeding any of them is a
+/// [`ParseError`] rather than a panic or stack overflow.
 #[derive(Default)]
-pub struct Parser {}
+pub struct Parser {
+    syntax: Syntax,
+    limits: ParseLimits,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ParseLimits {
+    max_nesting: usize,
+    max_repetition: u32,
+    max_nodes: usize,
+    max_set_range: u32,
+}
+
+impl Default for ParseLimits {
+    fn default() -> Self {
+        Self {
+            max_nesting: Parser::DEFAULT_MAX_NESTING,
+            max_repetition: Parser::DEFAULT_MAX_REPETITION,
+            max_nodes: Parser::DEFAULT_MAX_NODES,
+            max_set_range: Parser::DEFAULT_MAX_SET_RANGE,
+        }
+    }
+}
 
 struct ParserImpl<Iter>
 where
@@ -83,6 +220,21 @@ where
     index: usize,
 
     group_num: usize,
+    group_names: HashMap<String, usize>,
+
+    limits: ParseLimits,
+    depth: usize,
+    num_nodes: usize,
+
+    // Maps translated positions back to the original pattern for non-native syntaxes.
+    positions: Vec<(usize, usize)>,
+    last_word_ch_start: usize,
+    // A byte escape that ended the previous word, along with where it started.
+    pending_byte: Option<(u8, usize)>,
+    open_groups: Vec<usize>,
+    warnings: Vec<ParseWarning>,
+
+    arena: ParseArena,
 }
 
 impl<Iter> ParserImpl<Iter>
@@ -99,37 +251,52 @@ where
                 self.next();
                 match self.next() {
                     Some(':') => Some(GroupConfig::NonCapturing),
-                    Some('<') => {
-                        let mut name = String::new();
-                        while let (Some(ch), escaped) = self.next_escaped()? {
-                            if !escaped && ch == '>' {
-                                break;
+                    Some('<') => Some(self.parse_named_group_config('>')?),
+                    Some('\'') => Some(self.parse_named_group_config('\'')?),
+                    Some('P') => match self.next() {
+                        Some('<') => Some(self.parse_named_group_config('>')?),
+
+                        // (?P=name) is a backreference rather than a group.
+                        Some('=') => {
+                            let name = self.parse_group_name(')')?;
+                            let index = match self.group_names.get(&name) {
+                                None => return Err(ParseError::UnknownGroupName(name)),
+                                Some(index) => *index,
+                            };
+
+                            // A group can't refer to itself before it has finished capturing.
+                            if self.open_groups.contains(&index) {
+                                return Err(ParseError::BackreferenceToOpenGroup(name));
                             }
 
-                            name.push(ch);
+                            return Ok(ParseNodeVal::Backreference(index));
                         }
-
-                        Some(GroupConfig::Named(name))
-                    }
+                        _ => return Err(ParseError::BadGroupConfig),
+                    },
                     _ => return Err(ParseError::BadGroupConfig),
                 }
             }
 
-            // By default, just name the group its numeric position.
-            _ => {
-                let res = Some(GroupConfig::Named(format!("{}", self.group_num)));
-                self.group_num += 1;
-
-                res
-            }
+            // By default, groups are only identified by their numeric position.
+            _ => Some(GroupConfig::Capturing(self.next_capture(None))),
         };
 
-        let group = match self.parse(Some(')'))? {
+        if let Some(GroupConfig::Capturing(capture)) = &group_config {
+            self.open_groups.push(capture.index);
+        }
+
+        let group = match self.parse_nested(Some(')'))? {
             Some(group) => group,
-            None => return Err(ParseError::EmptyCaptureGroup),
+            None => self.empty_node(),
         };
 
-        self.next();
+        if let Some(GroupConfig::Capturing(_)) = &group_config {
+            self.open_groups.pop();
+        }
+
+        if self.next() != Some(')') {
+            return Err(ParseError::UnexpectedEndOfInput);
+        }
 
         Ok(ParseNodeVal::Group {
             group,
@@ -137,13 +304,60 @@ where
         })
     }
 
+    fn parse_named_group_config(&mut self, terminator: char) -> Result<GroupConfig, ParseError> {
+        let name = self.parse_group_name(terminator)?;
+        if self.group_names.contains_key(&name) {
+            return Err(ParseError::DuplicateGroupName(name));
+        }
+
+        let capture = self.next_capture(Some(name.clone()));
+        self.group_names.insert(name, capture.index);
+
+        Ok(GroupConfig::Capturing(capture))
+    }
+
+    /// Reads a group name up to (and including) `terminator`; names must be identifiers (`[A-Za-z_][A-Za-z0-9_]*`).
+    fn parse_group_name(&mut self, terminator: char) -> Result<String, ParseError> {
+        let mut name = String::new();
+        loop {
+            match self.next() {
+                None => return Err(ParseError::UnexpectedEndOfInput),
+                Some(ch) if ch == terminator => break,
+                Some(ch) => name.push(ch),
+            }
+        }
+
+        let mut chars = name.chars();
+        let is_identifier = match chars.next() {
+            Some(first) => {
+                (first.is_ascii_alphabetic() || first == '_') && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+            }
+            None => false,
+        };
+
+        if !is_identifier {
+            return Err(ParseError::InvalidGroupName(name));
+        }
+
+        Ok(name)
+    }
+
+    fn next_capture(&mut self, name: Option<String>) -> Capture {
+        let index = self.group_num;
+        self.group_num += 1;
+
+        Capture { index, name }
+    }
+
     fn parse_repetition_range_vals(&mut self) -> Result<(u32, Option<u32>), ParseError> {
         self.next();
 
         let mut min_str: Option<String> = None;
         let mut max_str: Option<String> = None;
+        let mut saw_comma = false;
 
-        while let Some(ch) = self.next() {
+        loop {
+            let ch = self.next().ok_or(ParseError::UnexpectedEndOfInput)?;
             match ch {
                 '0'..='9' => {
                     if let Some(ref mut min_str) = min_str {
@@ -154,7 +368,10 @@ where
                 }
                 '}' => break,
                 ',' => {
-                    while let Some(ch) = self.next() {
+                    saw_comma = true;
+
+                    loop {
+                        let ch = self.next().ok_or(ParseError::UnexpectedEndOfInput)?;
                         match ch {
                             '0'..='9' => {
                                 if let Some(ref mut max_str) = max_str {
@@ -174,12 +391,18 @@ where
             }
         }
 
-        let min = min_str
-            .ok_or(ParseError::MissingRepetitionRangeMin)?
-            .parse::<u32>()
-            .expect("should have caught bad u32 in parsing");
+        let parse_bound = |bound: String| match bound.parse::<u32>() {
+            Ok(bound) if bound <= self.limits.max_repetition => Ok(bound),
+            _ => Err(ParseError::RepetitionTooLarge(self.limits.max_repetition)),
+        };
+
+        let min = parse_bound(min_str.ok_or(ParseError::MissingRepetitionRangeMin)?)?;
 
-        let max = max_str.map(|max| max.parse::<u32>().expect("should have caught bad u32 in parsing"));
+        // "{n}" means exactly n, while "{n,}" has no upper bound.
+        let max = match (saw_comma, max_str) {
+            (false, _) => Some(min),
+            (true, max_str) => max_str.map(parse_bound).transpose()?,
+        };
 
         Ok((min, max))
     }
@@ -191,14 +414,38 @@ where
                 break;
             }
 
+            self.last_word_ch_start = self.index;
+
             let mut ch = self.next().unwrap();
             if ch == '\\' {
-                ch = self.escape_next()?;
+                if self.peek() != Some(&'x') {
+                    ch = self.escape_next()?;
+                } else {
+                    self.next();
+
+                    // Bytes outside of ASCII aren't chars, so they get a node of their own.
+                    match self.parse_hex_escape()? {
+                        byte if byte.is_ascii() => ch = char::from(byte),
+                        byte if word.is_empty() => return Ok(ParseNodeVal::Byte(byte)),
+                        byte => {
+                            self.pending_byte = Some((byte, self.last_word_ch_start));
+                            break;
+                        }
+                    }
+                }
             }
 
             word.push(ch);
         }
 
+        // A special char that can't start a token (like an unbalanced ')') would otherwise never be consumed.
+        if word.is_empty() {
+            return match self.next() {
+                None => Err(ParseError::UnexpectedEndOfInput),
+                Some(ch) => Err(ParseError::UnexpectedCharErr(ch)),
+            };
+        }
+
         Ok(ParseNodeVal::Word(word))
     }
 
@@ -206,92 +453,43 @@ where
         Self::SPECIAL_CHARS.contains(ch)
     }
 
-    fn decorate_node_option<F: FnOnce(Arc<RefCell<ParseNode>>) -> ParseNodeVal>(
-        node: &mut Option<Arc<RefCell<ParseNode>>>,
-        decorator: F,
-    ) -> Result<(), ParseError> {
-        if node.is_none() {
-            return Err(ParseError::UnexpectedEmptyParseNodeOption);
-        }
-
-        // Grab the node.
-        let orig_node = mem::take(node).unwrap();
-
-        // Grab the val of the original node.
-        let orig_node_val = mem::replace(&mut orig_node.as_ref().borrow_mut().val, ParseNodeVal::Poisoned);
-
-        let res_val = decorator(rcref(ParseNode {
-            val: orig_node_val,
-            next: None,
-        }));
-
-        // Swap the result value into the moved node.
-        orig_node.as_ref().borrow_mut().val = res_val;
-
-        // Swap the modified node back into the node addr.
-        mem::swap(node, &mut Some(orig_node));
-
-        Ok(())
+    fn decorate_node<F: FnOnce(ParseNodeId) -> ParseNodeVal>(&mut self, node: ParseNodeId, end: usize, decorator: F) {
+        // Wrap the node in place so whatever already links to it now links to the modifier.
+        self.arena.wrap(node, decorator);
+        self.arena[node].span.end = end;
     }
 
-    fn decorate_node_option_for_last_char_modifiers<F: FnOnce(Arc<RefCell<ParseNode>>) -> ParseNodeVal>(
-        node: &mut Option<Arc<RefCell<ParseNode>>>,
+    fn decorate_node_option_for_last_char_modifiers<F: FnOnce(ParseNodeId) -> ParseNodeVal>(
+        &mut self,
+        node: &mut Option<ParseNodeId>,
         decorator: F,
     ) -> Result<(), ParseError> {
-        let take_last_ch = match node {
-            Some(node) => match node.as_ref().borrow().val {
-                ParseNodeVal::Word(ref word) => word.len() > 1,
-                _ => false,
-            },
-            None => return Err(ParseError::MissingLeftSideOfModifier),
-        };
-
-        if !take_last_ch {
-            return Self::decorate_node_option(node, decorator);
-        }
-
-        let orig_node = mem::take(node).unwrap();
-
-        let orig_node_val = mem::replace(&mut orig_node.as_ref().borrow_mut().val, ParseNodeVal::Poisoned);
-
-        let (new_node_val_word, last_ch_as_str) = match orig_node_val {
-            ParseNodeVal::Word(word) => {
-                let mut new_node_val_word = String::new();
-                let mut last_ch_as_str = None;
-                let mut iter = word.chars().peekable();
+        let id = node.ok_or(ParseError::MissingLeftSideOfModifier)?;
+        let end = self.original_index(self.index);
 
-                while let Some(ch) = iter.next() {
-                    if iter.peek().is_none() {
-                        last_ch_as_str = Some(String::from(ch));
-                        break;
-                    }
-
-                    new_node_val_word.push(ch);
-                }
-
-                (
-                    new_node_val_word,
-                    last_ch_as_str.expect("should have found a last char in word"),
-                )
+        // A modifier after a word only applies to its last char, so split that char off into its own node.
+        let last_ch = match &mut self.arena[id].val {
+            ParseNodeVal::Word(word) if word.chars().count() > 1 => word.pop().expect("already checked the length"),
+            _ => {
+                self.decorate_node(id, end, decorator);
+                return Ok(());
             }
-            _ => unreachable!("already confirmed the value is a ParseNodeVal::Word"),
         };
 
-        let new_next = rcref(ParseNode {
-            val: decorator(rcref(ParseNode {
-                val: ParseNodeVal::Word(last_ch_as_str),
-                next: None,
-            })),
-            next: None,
-        });
+        let last_ch_start = self.original_index(self.last_word_ch_start);
+        let last_ch_span = Span {
+            start: last_ch_start,
+            end: self.arena[id].span.end,
+        };
+
+        let last = self.arena.alloc(ParseNodeVal::Word(last_ch.to_string()), last_ch_span);
+        self.decorate_node(last, end, decorator);
 
-        // Swap in the new node value and node.next (which will be swapped into the node addr).
-        let mut orig_node_mut = orig_node.as_ref().borrow_mut();
-        orig_node_mut.val = ParseNodeVal::Word(new_node_val_word);
-        orig_node_mut.next = Some(new_next.clone());
+        let orig_node = &mut self.arena[id];
+        orig_node.next = Some(last);
+        orig_node.span.end = last_ch_start;
 
-        // Swap in the new node.
-        mem::swap(node, &mut Some(new_next));
+        *node = Some(last);
 
         Ok(())
     }
@@ -307,23 +505,45 @@ where
         true
     }
 
-    fn parse(&mut self, until: Option<char>) -> Result<Option<Arc<RefCell<ParseNode>>>, ParseError> {
+    /// Parses a sub-expression (a group), enforcing the nesting limit.
+    fn parse_nested(&mut self, until: Option<char>) -> Result<Option<ParseNodeId>, ParseError> {
+        if self.depth == self.limits.max_nesting {
+            return Err(ParseError::NestingTooDeep(self.limits.max_nesting));
+        }
+
+        self.depth += 1;
+        let res = self.parse(until);
+        self.depth -= 1;
+
+        res
+    }
+
+    fn parse(&mut self, until: Option<char>) -> Result<Option<ParseNodeId>, ParseError> {
         let mut head = None;
-        let mut prev: Option<Arc<RefCell<ParseNode>>> = None;
+        let mut prev: Option<ParseNodeId> = None;
+        let mut branches = vec![];
 
-        while let Some(ch) = self.peek() {
+        while let Some(&ch) = self.peek() {
             if let Some(until) = until {
-                if *ch == until {
+                if ch == until {
                     break;
                 }
             }
 
+            // Every token either creates or decorates a node.
+            self.num_nodes += 1;
+            if self.num_nodes > self.limits.max_nodes {
+                return Err(ParseError::TooManyNodes(self.limits.max_nodes));
+            }
+
+            let start = self.index;
+
             let new_node_val = match ch {
                 '{' => {
                     // Parse the repetition range vals.
                     let (min, max) = self.parse_repetition_range_vals()?;
 
-                    Self::decorate_node_option_for_last_char_modifiers(&mut prev, |old_prev| {
+                    self.decorate_node_option_for_last_char_modifiers(&mut prev, |old_prev| {
                         ParseNodeVal::RepetitionRange {
                             min,
                             max,
@@ -334,29 +554,15 @@ where
                     continue;
                 }
                 '|' => {
-                    self.next();
-
-                    // Grab the head of the current parse group and consider everything under it the left side.
-                    let left = mem::take(&mut head);
-
-                    // Parse everything after the "or" as a separate group and consider it the right side.
-                    let right = match self.parse(Some(')'))? {
-                        None => return Err(ParseError::MissingRightSideOfOr),
-                        Some(right) => right,
-                    };
-
-                    // Construct the result.
-                    let res_val = ParseNodeVal::Or {
-                        left: left.unwrap(),
-                        right,
+                    // Everything since the last "|" is a branch; concatenation binds tighter than alternation.
+                    let branch = match head.take() {
+                        Some(branch) => branch,
+                        None => self.empty_node(),
                     };
+                    branches.push(branch);
+                    prev = None;
 
-                    let new_head = rcref(ParseNode {
-                        val: res_val.clone(),
-                        next: None,
-                    });
-                    mem::swap(&mut head, &mut Some(new_head.clone()));
-                    mem::swap(&mut prev, &mut Some(new_head));
+                    self.next();
 
                     continue;
                 }
@@ -373,19 +579,30 @@ where
 
                     let mut found_end = false;
                     let mut set = indexmap::IndexSet::new();
+                    let mut duplicates = indexmap::IndexSet::new();
                     while let (Some(ch), escaped) = self.next_escaped()? {
                         if !escaped && ch == ']' {
                             found_end = true;
                             break;
                         }
 
-                        set.insert(ch);
+                        if !set.insert(ch) {
+                            duplicates.insert(ch);
+                        }
                     }
 
                     if !found_end {
                         return Err(ParseError::UnterminatedCharSet);
                     }
 
+                    let span = self.span(start, self.index);
+                    for member in duplicates {
+                        self.warnings.push(ParseWarning {
+                            kind: ParseWarningKind::DuplicateSetMember(member),
+                            span,
+                        });
+                    }
+
                     ParseNodeVal::Set { set, inverted }
                 }
                 '.' => {
@@ -397,7 +614,7 @@ where
                     self.next();
 
                     let greedy = self.chomp_greediness();
-                    Self::decorate_node_option_for_last_char_modifiers(&mut prev, |old_node| {
+                    self.decorate_node_option_for_last_char_modifiers(&mut prev, |old_node| {
                         ParseNodeVal::ZeroOrMore { node: old_node, greedy }
                     })?;
 
@@ -407,8 +624,9 @@ where
                     self.next();
 
                     let greedy = self.chomp_greediness();
-                    Self::decorate_node_option_for_last_char_modifiers(&mut prev, |old_node| {
-                        ParseNodeVal::OneOrMore { node: old_node, greedy }
+                    self.decorate_node_option_for_last_char_modifiers(&mut prev, |old_node| ParseNodeVal::OneOrMore {
+                        node: old_node,
+                        greedy,
                     })?;
 
                     continue;
@@ -416,7 +634,7 @@ where
                 '?' => {
                     self.next();
 
-                    Self::decorate_node_option_for_last_char_modifiers(&mut prev, |old_node| {
+                    self.decorate_node_option_for_last_char_modifiers(&mut prev, |old_node| {
                         ParseNodeVal::Optional(old_node)
                     })?;
 
@@ -436,25 +654,72 @@ where
                 _ => self.parse_word()?,
             };
 
-            let new_node = rcref(ParseNode {
-                val: new_node_val,
-                next: None,
-            });
+            // A byte escape that ended a word gets its own node right after it.
+            let pending_byte = self.pending_byte.take();
+
+            let span = self.span(start, pending_byte.map_or(self.index, |(_, byte_start)| byte_start));
+            let new_node = self.arena.alloc(new_node_val, span);
+
+            match prev {
+                None => head = Some(new_node),
+                Some(prev) => self.arena[prev].next = Some(new_node),
+            }
+
+            prev = Some(new_node);
 
-            if prev.is_none() {
-                head = Some(new_node.clone());
-                prev = Some(new_node);
-            } else {
-                // Update node.next to point to the new node.
-                let node_val = mem::take(&mut prev).unwrap();
-                (*node_val).borrow_mut().next = Some(new_node.clone());
+            if let Some((byte, byte_start)) = pending_byte {
+                let span = self.span(byte_start, self.index);
+                let byte_node = self.arena.alloc(ParseNodeVal::Byte(byte), span);
 
-                // Update node to point to the new node.
-                prev = Some(new_node);
+                self.arena[new_node].next = Some(byte_node);
+                prev = Some(byte_node);
             }
         }
 
-        Ok(head)
+        if branches.is_empty() {
+            return Ok(head);
+        }
+
+        let branch = match head {
+            Some(branch) => branch,
+            None => self.empty_node(),
+        };
+        branches.push(branch);
+
+        let span = Span {
+            start: self.arena[branches[0]].span.start,
+            end: self.original_index(self.index),
+        };
+
+        Ok(Some(self.arena.alloc(ParseNodeVal::Or(branches), span)))
+    }
+
+    /// Maps a count of consumed chars to a count of consumed chars in the original (untranslated) pattern.
+    fn original_index(&self, index: usize) -> usize {
+        match (index, self.positions.last()) {
+            (0, _) | (_, None) => index,
+            (_, Some(last)) => self.positions.get(index - 1).unwrap_or(last).1,
+        }
+    }
+
+    /// Allocates a zero-width node (which always matches) for an empty group or alternative at the current position.
+    fn empty_node(&mut self) -> ParseNodeId {
+        let span = self.span(self.index, self.index);
+
+        self.arena.alloc(ParseNodeVal::Word(String::new()), span)
+    }
+
+    fn span(&self, start: usize, end: usize) -> Span {
+        let start = match self.positions.get(start) {
+            None if self.positions.is_empty() => start,
+            None => self.original_index(start),
+            Some((token_start, _)) => *token_start,
+        };
+
+        Span {
+            start,
+            end: self.original_index(end),
+        }
     }
 
     fn peek(&mut self) -> Option<&char> {
@@ -481,52 +746,221 @@ where
             None => Err(ParseError::MissingCharacterToEscape),
             Some(ch) => match ch {
                 '(' | ')' | '{' | '}' | '[' | ']' | '|' | '\\' | '^' | '$' | '.' | '*' | '?' | '+' => Ok(ch),
+                // Sets only hold chars, so only bytes that are ASCII chars can be members.
+                'x' => match self.parse_hex_escape()? {
+                    byte if byte.is_ascii() => Ok(char::from(byte)),
+                    byte => Err(ParseError::ByteInSet(byte)),
+                },
                 _ => Err(ParseError::UnexpectedCharErr(ch)),
             },
         }
     }
+
+    /// Parses the two hex digits of a `\xHH` escape.
+    fn parse_hex_escape(&mut self) -> Result<u8, ParseError> {
+        let mut byte = 0;
+        for _ in 0..2 {
+            let digit = match self.next() {
+                None => return Err(ParseError::MissingCharacterToEscape),
+                Some(ch) => ch.to_digit(16).ok_or(ParseError::UnexpectedCharErr(ch))?,
+            };
+
+            byte = byte * 16 + digit as u8;
+        }
+
+        Ok(byte)
+    }
 }
 
 impl Parser {
     pub fn new() -> Self {
-        Parser {}
+        Parser::default()
+    }
+
+    pub const DEFAULT_MAX_NESTING: usize = 250;
+    pub const DEFAULT_MAX_REPETITION: u32 = 1000;
+    pub const DEFAULT_MAX_NODES: usize = 10_000;
+    pub const DEFAULT_MAX_SET_RANGE: u32 = 0x10000;
+
+    /// Creates a parser for patterns written in the given dialect.
+    pub fn with_syntax(syntax: Syntax) -> Self {
+        Parser {
+            syntax,
+            ..Default::default()
+        }
+    }
+
+    /// Sets how deeply groups and alternations may nest.
+    pub fn max_nesting(mut self, max_nesting: usize) -> Self {
+        self.limits.max_nesting = max_nesting;
+        self
+    }
+
+    /// Sets the largest count allowed in a repetition range like `{min,max}`.
+    pub fn max_repetition(mut self, max_repetition: u32) -> Self {
+        self.limits.max_repetition = max_repetition;
+        self
+    }
+
+    /// Sets the largest number of nodes a pattern may compile to.
+    pub fn max_nodes(mut self, max_nodes: usize) -> Self {
+        self.limits.max_nodes = max_nodes;
+        self
+    }
+
+    /// Sets how many chars a bracket range like `[a-z]` may cover in the POSIX and glob syntaxes, which expand ranges
+    /// into their members (the default covers the whole Basic Multilingual Plane).
+    pub fn max_set_range(mut self, max_set_range: u32) -> Self {
+        self.limits.max_set_range = max_set_range;
+        self
     }
 
     pub fn parse_str<'str>(&self, input: &'str str) -> Result<ParseResult, ParseErrorWithContext<'str>> {
+        self.parse_iter(input.chars()).map_err(|err| ParseErrorWithContext {
+            err: err.err,
+            str: input,
+            cur: err.position,
+        })
+    }
+
+    /// Parses a pattern from any source of chars.
+    ///
+    /// Native patterns are parsed as the chars come in; other syntaxes are collected up front so they can be translated.
+    pub fn parse_iter<I: IntoIterator<Item = char>>(&self, input: I) -> Result<ParseResult, PositionedParseError> {
+        let res = match self.syntax {
+            Syntax::Rustex => self.parse_native(input.into_iter(), vec![]),
+            _ => syntax::translate(
+                self.syntax,
+                &input.into_iter().collect::<String>(),
+                self.limits.max_set_range,
+            )
+            .and_then(|translated| self.parse_native(translated.pattern.chars(), translated.positions)),
+        };
+
+        res.map_err(|(err, position)| PositionedParseError { err, position })
+    }
+
+    /// Parses a UTF-8 pattern from a reader without reading it all into memory first (for native patterns).
+    pub fn parse_reader<R: io::Read>(&self, reader: R) -> Result<ParseResult, ReadParseError> {
+        let chars = reader::CharReader::new(reader);
+        let read_err = chars.err.clone();
+
+        let res = self.parse_iter(chars);
+
+        // A read error cuts the pattern short, so it explains whatever parse error that caused.
+        if let Some(err) = read_err.take() {
+            return Err(ReadParseError::Io(err));
+        }
+
+        res.map_err(ReadParseError::Parse)
+    }
+
+    /// Parses a pattern in rustex syntax; errors carry the number of chars of the original pattern consumed.
+    fn parse_native<Iter: Iterator<Item = char>>(
+        &self,
+        input: Iter,
+        positions: Vec<(usize, usize)>,
+    ) -> Result<ParseResult, (ParseError, usize)> {
         let mut parser = ParserImpl {
-            iter: input.chars().peekable(),
+            iter: input.peekable(),
             index: 0,
             group_num: 1,
+            group_names: HashMap::new(),
+            limits: self.limits,
+            depth: 0,
+            num_nodes: 0,
+            positions,
+            last_word_ch_start: 0,
+            pending_byte: None,
+            open_groups: vec![],
+            warnings: vec![],
+            arena: ParseArena::default(),
         };
 
-        Ok(ParseResult {
-            head: parser
-                .parse(None)
-                .and_then(|maybe_head| {
-                    let head_ptr = match maybe_head {
-                        None => return Ok(None),
-                        Some(head) => head,
-                    };
+        let head = parser
+            .parse(None)
+            .map_err(|err| (err, parser.original_index(parser.index)))?
+            .map(|head| Arc::new(mem::take(&mut parser.arena).into_node(head)));
 
-                    let head = Arc::try_unwrap(head_ptr)
-                        .map_err(|_| ParseError::ParseGraphCycle)?
-                        .into_inner()
-                        .try_into()?;
-
-                    Ok(Some(Arc::new(head)))
-                })
-                .map_err(|err| ParseErrorWithContext {
-                    err,
-                    str: input,
-                    cur: parser.index,
-                })?,
-        })
+        let mut warnings = parser.warnings;
+        if let Some(head) = &head {
+            warnings.extend(validate::validate(head).map_err(|(err, span)| (err, span.end))?);
+        }
+
+        warnings.sort_by_key(|warning| warning.span.start);
+
+        Ok(ParseResult { head, warnings })
     }
 }
 
+/// The result of parsing a pattern.
+///
+/// With the `serde` feature enabled this can be serialized and deserialized to cache compiled patterns.
+///
+/// Internally a pattern is an `Arc`-linked list of `Node`s, but that shape is an implementation detail, so the
+/// serialized form flattens every chain of nodes into a sequence of `NodeVal`s:
+///
+/// ```text
+/// ParseResult = { "head": null | Chain }
+/// Chain       = [ NodeVal, ... ]                      (at least one element)
+/// NodeVal     = { "kind": "word",    "value": "foo" }
+///             | { "kind": "any" }
+///             | { "kind": "start" } | { "kind": "end" }
+///             | { "kind": "zero_or_more", "value": { "node": Chain, "greedy": bool } }
+///             | { "kind": "one_or_more",  "value": { "node": Chain, "greedy": bool } }
+///             | { "kind": "optional",     "value": Chain }
+///             | { "kind": "group",        "value": { "group": Chain, "cfg": null | GroupConfig } }
+///             | { "kind": "set",          "value": { "set": [char, ...], "inverted": bool } }
+///             | { "kind": "or",           "value": [Chain, ...] }
+///             | { "kind": "repetition_range", "value": { "min": u32, "max": null | u32, "node": Chain } }
+///             | { "kind": "backreference", "value": usize }
+///             | { "kind": "byte",         "value": u8 }
+/// GroupConfig = "non_capturing" | { "capturing": { "index": usize, "name": null | "name" } }
+/// ```
+///
+/// Executor-internal values (group end markers) are never part of a parse result and cannot be
+/// serialized. Node spans and parse warnings aren't serialized either.
+///
+/// Deserializing rejects graphs the parser could never produce: an `or` without branches, a repetition range whose
+/// `min` exceeds its `max` and a backreference to a group that doesn't exist.
 #[derive(Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct ParseResult {
     pub head: Option<Arc<Node>>,
+
+    #[cfg_attr(feature = "serde", serde(skip))]
+    warnings: Vec<ParseWarning>,
+}
+
+impl ParseResult {
+    /// Returns the warnings produced while parsing and validating the pattern, in pattern order.
+    pub fn warnings(&self) -> &[ParseWarning] {
+        &self.warnings
+    }
+
+    /// Lists the capturing groups defined by the pattern in the order their opening parens appear.
+    pub fn captures(&self) -> Vec<Capture> {
+        let mut captures = vec![];
+
+        if let Some(head) = &self.head {
+            head.walk(&mut |node| {
+                if let NodeVal::Group {
+                    cfg: Some(GroupConfig::Capturing(capture)),
+                    ..
+                } = &node.val
+                {
+                    captures.push(capture.clone());
+                }
+            });
+        }
+
+        captures
+    }
+
+    /// Returns the number of capturing groups defined by the pattern.
+    pub fn capture_count(&self) -> usize {
+        self.captures().len()
+    }
 }
 
 impl fmt::Debug for ParseResult {
@@ -1,21 +1,70 @@
-use std::{cell::RefCell, fmt, sync::Arc};
+use std::{fmt, sync::Arc};
 
 use indexmap::IndexSet;
 
-use crate::parser::ParseNodeVal;
-
-use super::ParseNode;
-
 #[derive(Clone)]
 pub struct Node {
     pub val: NodeVal,
     pub next: Option<Arc<Node>>,
+
+    /// Where the node came from in the original pattern.
+    pub span: Span,
+}
+
+/// A range of char offsets into a pattern (`end` is exclusive).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+}
+
+impl Node {
+    /// Iterates over this node and every node chained after it via `next`.
+    pub fn iter(&self) -> NodeIter<'_> {
+        NodeIter { node: Some(self) }
+    }
+
+    /// Visits this chain and every sub-expression depth-first, in the order the nodes appear in the pattern.
+    pub fn walk<F: FnMut(&Node)>(&self, f: &mut F) {
+        for node in self.iter() {
+            f(node);
+
+            for child in node.val.children() {
+                child.walk(f);
+            }
+        }
+    }
+
+    /// Whether this chain can match without consuming any input.
+    pub fn matches_empty(&self) -> bool {
+        self.iter().all(|node| node.val.matches_empty())
+    }
+}
+
+pub struct NodeIter<'a> {
+    node: Option<&'a Node>,
+}
+
+impl<'a> Iterator for NodeIter<'a> {
+    type Item = &'a Node;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.node?;
+        self.node = node.next.as_deref();
+
+        Some(node)
+    }
 }
 
 impl fmt::Debug for Node {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match &self.val {
-            NodeVal::Poisoned => f.write_str("!!poison!!"),
             NodeVal::Word(word) => f.write_fmt(format_args!("'{}'", word)),
             NodeVal::Any => f.write_str("."),
             NodeVal::ZeroOrMore { node, greedy } => {
@@ -47,9 +96,10 @@ impl fmt::Debug for Node {
 
                 match cfg {
                     None => {}
-                    Some(super::GroupConfig::Named(name)) => {
-                        f.write_fmt(format_args!("<{}>", name))?;
-                    }
+                    Some(super::GroupConfig::Capturing(capture)) => match &capture.name {
+                        Some(name) => f.write_fmt(format_args!("<{}>", name))?,
+                        None => f.write_fmt(format_args!("<{}>", capture.index))?,
+                    },
                     Some(super::GroupConfig::NonCapturing) => {
                         f.write_str("?:")?;
                     }
@@ -78,32 +128,39 @@ impl fmt::Debug for Node {
 
                 f.write_str("]")
             }
-            NodeVal::Or { left, right } => {
-                f.write_str("<")?;
-                left.fmt(f)?;
-                f.write_str(">|<")?;
-                right.fmt(f)?;
-                f.write_str(">")
+            NodeVal::Or(branches) => {
+                let mut iter = branches.iter().peekable();
+                while let Some(branch) = iter.next() {
+                    f.write_str("<")?;
+                    branch.fmt(f)?;
+                    f.write_str(">")?;
+
+                    if iter.peek().is_some() {
+                        f.write_str("|")?;
+                    }
+                }
+
+                Ok(())
             }
             NodeVal::RepetitionRange { min, max, node } => {
                 node.fmt(f)?;
 
-                f.write_str("{")?;
-                f.write_fmt(format_args!("{}", min))?;
-
-                if let Some(max) = max {
-                    f.write_fmt(format_args!(",{}", max))?;
+                match max {
+                    None => f.write_fmt(format_args!("{{{},}}", min))?,
+                    Some(max) if max == min => f.write_fmt(format_args!("{{{}}}", min))?,
+                    Some(max) => f.write_fmt(format_args!("{{{},{}}}", min, max))?,
                 }
 
-                f.write_str("}")?;
-
                 Ok(())
             }
+            NodeVal::Backreference(index) => f.write_fmt(format_args!("\\{}", index)),
+            NodeVal::Byte(byte) => f.write_fmt(format_args!("\\x{:02X}", byte)),
             NodeVal::GroupEnd { .. } => {
                 f.write_str("(/)")?;
 
                 Ok(())
             }
+            NodeVal::IterationEnd { .. } => f.write_str("(/*)"),
         }?;
 
         match &self.next {
@@ -116,62 +173,57 @@ impl fmt::Debug for Node {
     }
 }
 
-impl TryFrom<ParseNode> for Node {
-    type Error = super::ParseError;
-
-    fn try_from(parsed_node: ParseNode) -> Result<Self, Self::Error> {
-        fn try_unwrap_parse_node(parse_node: Arc<RefCell<ParseNode>>) -> Result<ParseNode, super::ParseError> {
-            Arc::try_unwrap(parse_node)
-                .map_err(|_| super::ParseError::ParseGraphCycle)
-                .map(|refcell| refcell.into_inner())
+impl NodeVal {
+    /// Returns the heads of the sub-expressions nested directly inside this value.
+    pub fn children(&self) -> Vec<&Arc<Node>> {
+        match self {
+            NodeVal::Word(_)
+            | NodeVal::Any
+            | NodeVal::Start
+            | NodeVal::End
+            | NodeVal::GroupEnd { .. }
+            | NodeVal::IterationEnd { .. }
+            | NodeVal::Set { .. }
+            | NodeVal::Backreference(_)
+            | NodeVal::Byte(_) => vec![],
+            NodeVal::ZeroOrMore { node, .. }
+            | NodeVal::OneOrMore { node, .. }
+            | NodeVal::Optional(node)
+            | NodeVal::RepetitionRange { node, .. } => vec![node],
+            NodeVal::Group { group, .. } => vec![group],
+            NodeVal::Or(branches) => branches.iter().collect(),
         }
+    }
 
-        let val = match parsed_node.val {
-            ParseNodeVal::Poisoned => NodeVal::Poisoned,
-            ParseNodeVal::Word(word) => NodeVal::Word(word),
-            ParseNodeVal::Any => NodeVal::Any,
-            ParseNodeVal::ZeroOrMore { node, greedy } => NodeVal::ZeroOrMore {
-                node: Arc::new(try_unwrap_parse_node(node)?.try_into()?),
-                greedy,
-            },
-            ParseNodeVal::OneOrMore { node, greedy } => NodeVal::OneOrMore {
-                node: Arc::new(try_unwrap_parse_node(node)?.try_into()?),
-                greedy,
-            },
-            ParseNodeVal::Start => NodeVal::Start,
-            ParseNodeVal::End => NodeVal::End,
-            ParseNodeVal::Optional(node) => NodeVal::Optional(Arc::new(try_unwrap_parse_node(node)?.try_into()?)),
-            ParseNodeVal::Group { group, cfg } => NodeVal::Group {
-                group: Arc::new(try_unwrap_parse_node(group)?.try_into()?),
-                cfg,
-            },
-            ParseNodeVal::Set { set, inverted } => NodeVal::Set { set, inverted },
-            ParseNodeVal::Or { left, right } => NodeVal::Or {
-                left: Arc::new(try_unwrap_parse_node(left)?.try_into()?),
-                right: Arc::new(try_unwrap_parse_node(right)?.try_into()?),
-            },
-            ParseNodeVal::RepetitionRange { min, max, node } => NodeVal::RepetitionRange {
-                min,
-                max,
-                node: Arc::new(try_unwrap_parse_node(node)?.try_into()?),
-            },
-        };
-
-        Ok(Node {
-            val,
-            next: match parsed_node.next {
-                None => None,
-                Some(next) => Some(Arc::new(try_unwrap_parse_node(next)?.try_into()?)),
-            },
-        })
+    /// Whether this value (ignoring the rest of its chain) can match without consuming any input.
+    ///
+    /// Backreferences are assumed to match empty since the group they refer to might have.
+    pub fn matches_empty(&self) -> bool {
+        match self {
+            NodeVal::Word(word) => word.is_empty(),
+            NodeVal::Any | NodeVal::Set { .. } | NodeVal::Byte(_) => false,
+            NodeVal::OneOrMore { node, .. } => node.matches_empty(),
+            NodeVal::RepetitionRange { min, node, .. } => *min == 0 || node.matches_empty(),
+            NodeVal::Group { group, .. } => group.matches_empty(),
+            NodeVal::Or(branches) => branches.iter().any(|branch| branch.matches_empty()),
+            NodeVal::Start
+            | NodeVal::End
+            | NodeVal::ZeroOrMore { .. }
+            | NodeVal::Optional(_)
+            | NodeVal::GroupEnd { .. }
+            | NodeVal::IterationEnd { .. }
+            | NodeVal::Backreference(_) => true,
+        }
     }
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "kind", content = "value", rename_all = "snake_case")
+)]
 pub enum NodeVal {
-    // Poisoned is a special value that represents a NodeVal that has been poisoned.
-    Poisoned,
-
     Word(String),
     Any,
     ZeroOrMore {
@@ -189,21 +241,29 @@ pub enum NodeVal {
         group: Arc<Node>,
         cfg: Option<super::GroupConfig>,
     },
+    #[cfg_attr(feature = "serde", serde(skip))]
     GroupEnd {
         start: usize,
         cfg: Option<super::GroupConfig>,
     },
+    /// Marks the end of one iteration of a repetition that started at `start`; `next` is the repetition itself.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    IterationEnd {
+        start: usize,
+    },
     Set {
         set: IndexSet<char>,
         inverted: bool,
     },
-    Or {
-        left: Arc<Node>,
-        right: Arc<Node>,
-    },
+    /// Alternatives in the order they're tried.
+    Or(Vec<Arc<Node>>),
     RepetitionRange {
         min: u32,
         max: Option<u32>,
         node: Arc<Node>,
     },
+    Backreference(usize),
+    /// A byte above ASCII written as `\xHH` (outside of a set): a raw byte when matching bytes, or the char with that
+    /// code point when matching a str.
+    Byte(u8),
 }
@@ -1,45 +1,158 @@
-use std::{cell::RefCell, sync::Arc};
+use std::{
+    mem,
+    ops::{Index, IndexMut},
+    sync::Arc,
+};
 
 use indexmap::IndexSet;
 
+use super::{Node, NodeVal, Span};
+
+/// Identifies a node in a [`ParseArena`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseNodeId(usize);
+
+/// Owns every node built while parsing a pattern.
+///
+/// Nodes refer to each other by id, so the parser can rewire them (to splice in a modifier, say) without having to
+/// take shared nodes apart.
+#[derive(Default)]
+pub struct ParseArena {
+    nodes: Vec<ParseNode>,
+}
+
+impl ParseArena {
+    pub fn alloc(&mut self, val: ParseNodeVal, span: Span) -> ParseNodeId {
+        self.nodes.push(ParseNode { val, next: None, span });
+
+        ParseNodeId(self.nodes.len() - 1)
+    }
+
+    /// Moves the value of `id` into a new node and replaces it with `wrap(new node)`, which keeps every link to `id`
+    /// pointing at the wrapper.
+    pub fn wrap<F: FnOnce(ParseNodeId) -> ParseNodeVal>(&mut self, id: ParseNodeId, wrap: F) {
+        let inner = ParseNodeId(self.nodes.len());
+        let wrapper = wrap(inner);
+
+        let node = &mut self.nodes[id.0];
+        let val = mem::replace(&mut node.val, wrapper);
+        let span = node.span;
+
+        self.nodes.push(ParseNode { val, next: None, span });
+    }
+
+    /// Converts the chain starting at `head` into `Node`s, consuming the arena.
+    pub fn into_node(self, head: ParseNodeId) -> Node {
+        let mut nodes = self.nodes.into_iter().map(Some).collect::<Vec<_>>();
+
+        convert_chain(&mut nodes, head)
+    }
+}
+
+impl Index<ParseNodeId> for ParseArena {
+    type Output = ParseNode;
+
+    fn index(&self, id: ParseNodeId) -> &Self::Output {
+        &self.nodes[id.0]
+    }
+}
+
+impl IndexMut<ParseNodeId> for ParseArena {
+    fn index_mut(&mut self, id: ParseNodeId) -> &mut Self::Output {
+        &mut self.nodes[id.0]
+    }
+}
+
+fn convert_chain(nodes: &mut [Option<ParseNode>], head: ParseNodeId) -> Node {
+    // Walk the chain first so long patterns don't recurse once per node.
+    let mut chain = vec![head];
+    while let Some(next) = nodes[chain[chain.len() - 1].0].as_ref().and_then(|node| node.next) {
+        chain.push(next);
+    }
+
+    let mut res: Option<Node> = None;
+    for id in chain.into_iter().rev() {
+        let node = nodes[id.0]
+            .take()
+            .expect("every parse node belongs to exactly one chain");
+
+        res = Some(Node {
+            val: convert_val(nodes, node.val),
+            next: res.map(Arc::new),
+            span: node.span,
+        });
+    }
+
+    res.expect("a chain always has a head")
+}
+
+fn convert_val(nodes: &mut [Option<ParseNode>], val: ParseNodeVal) -> NodeVal {
+    let mut convert = |id| Arc::new(convert_chain(nodes, id));
+
+    match val {
+        ParseNodeVal::Word(word) => NodeVal::Word(word),
+        ParseNodeVal::Any => NodeVal::Any,
+        ParseNodeVal::ZeroOrMore { node, greedy } => NodeVal::ZeroOrMore {
+            node: convert(node),
+            greedy,
+        },
+        ParseNodeVal::OneOrMore { node, greedy } => NodeVal::OneOrMore {
+            node: convert(node),
+            greedy,
+        },
+        ParseNodeVal::Start => NodeVal::Start,
+        ParseNodeVal::End => NodeVal::End,
+        ParseNodeVal::Optional(node) => NodeVal::Optional(convert(node)),
+        ParseNodeVal::Group { group, cfg } => NodeVal::Group {
+            group: convert(group),
+            cfg,
+        },
+        ParseNodeVal::Set { set, inverted } => NodeVal::Set { set, inverted },
+        ParseNodeVal::Or(branches) => NodeVal::Or(branches.into_iter().map(convert).collect()),
+        ParseNodeVal::RepetitionRange { min, max, node } => NodeVal::RepetitionRange {
+            min,
+            max,
+            node: convert(node),
+        },
+        ParseNodeVal::Backreference(index) => NodeVal::Backreference(index),
+        ParseNodeVal::Byte(byte) => NodeVal::Byte(byte),
+    }
+}
+
 pub struct ParseNode {
     pub val: ParseNodeVal,
-    pub next: Option<Arc<RefCell<ParseNode>>>,
+    pub next: Option<ParseNodeId>,
+    pub span: Span,
 }
 
-#[derive(Clone)]
 pub enum ParseNodeVal {
-    // Poisoned is a special value that represents a NodeVal that has been poisoned.
-    Poisoned,
-
     Word(String),
     Any,
     ZeroOrMore {
-        node: Arc<RefCell<ParseNode>>,
+        node: ParseNodeId,
         greedy: bool,
     },
     OneOrMore {
-        node: Arc<RefCell<ParseNode>>,
+        node: ParseNodeId,
         greedy: bool,
     },
     Start,
     End,
-    Optional(Arc<RefCell<ParseNode>>),
+    Optional(ParseNodeId),
     Group {
-        group: Arc<RefCell<ParseNode>>,
+        group: ParseNodeId,
         cfg: Option<super::GroupConfig>,
     },
     Set {
         set: IndexSet<char>,
         inverted: bool,
     },
-    Or {
-        left: Arc<RefCell<ParseNode>>,
-        right: Arc<RefCell<ParseNode>>,
-    },
+    Or(Vec<ParseNodeId>),
     RepetitionRange {
         min: u32,
         max: Option<u32>,
-        node: Arc<RefCell<ParseNode>>,
+        node: ParseNodeId,
     },
+    Backreference(usize),
+    Byte(u8),
 }
@@ -0,0 +1,80 @@
+use std::{
+    cell::RefCell,
+    io::{self, BufRead, BufReader, Read},
+    rc::Rc,
+};
+
+/// Decodes UTF-8 chars from a reader one at a time.
+///
+/// The iterator stops at the first read or decoding error, which is stashed in `err` so the caller can tell a
+/// truncated pattern from a broken reader.
+pub(super) struct CharReader<R: Read> {
+    reader: BufReader<R>,
+    pub err: Rc<RefCell<Option<io::Error>>>,
+}
+
+impl<R: Read> CharReader<R> {
+    pub fn new(reader: R) -> Self {
+        CharReader {
+            reader: BufReader::new(reader),
+            err: Rc::new(RefCell::new(None)),
+        }
+    }
+
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        let byte = match self.reader.fill_buf()?.first() {
+            None => return Ok(None),
+            Some(byte) => *byte,
+        };
+
+        self.reader.consume(1);
+
+        Ok(Some(byte))
+    }
+
+    fn read_char(&mut self) -> io::Result<Option<char>> {
+        let first = match self.read_byte()? {
+            None => return Ok(None),
+            Some(byte) => byte,
+        };
+
+        let len = match first {
+            0x00..=0x7f => 1,
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf7 => 4,
+            _ => return Err(invalid_utf8()),
+        };
+
+        let mut bytes = [first, 0, 0, 0];
+        for byte in bytes.iter_mut().take(len).skip(1) {
+            *byte = self.read_byte()?.ok_or_else(invalid_utf8)?;
+        }
+
+        std::str::from_utf8(&bytes[..len])
+            .map_err(|_| invalid_utf8())
+            .map(|str| str.chars().next())
+    }
+}
+
+impl<R: Read> Iterator for CharReader<R> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.err.borrow().is_some() {
+            return None;
+        }
+
+        match self.read_char() {
+            Ok(ch) => ch,
+            Err(err) => {
+                self.err.replace(Some(err));
+                None
+            }
+        }
+    }
+}
+
+fn invalid_utf8() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "pattern is not valid UTF-8")
+}
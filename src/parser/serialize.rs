@@ -0,0 +1,89 @@
+//! Serde support for parse graphs.
+//!
+//! The schema is documented on [`super::ParseResult`].
+
+use std::{collections::HashSet, sync::Arc};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{Node, NodeVal, ParseResult};
+
+impl Serialize for Node {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter().map(|node| &node.val))
+    }
+}
+
+impl<'de> Deserialize<'de> for Node {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let vals = Vec::<NodeVal>::deserialize(deserializer)?;
+
+        // Build the chain back-to-front so each node can take ownership of its tail.
+        let mut next = None;
+        for val in vals.into_iter().rev() {
+            next = Some(Arc::new(Node {
+                val,
+                next,
+                span: Default::default(),
+            }));
+        }
+
+        match next {
+            None => Err(de::Error::invalid_length(0, &"at least one node")),
+            Some(head) => Ok(Arc::try_unwrap(head).expect("freshly built node chain should not be shared")),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ParseResult {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Raw {
+            head: Option<Arc<Node>>,
+        }
+
+        let parsed = ParseResult {
+            head: Raw::deserialize(deserializer)?.head,
+            ..Default::default()
+        };
+
+        check_invariants(&parsed).map_err(de::Error::custom)?;
+
+        Ok(parsed)
+    }
+}
+
+/// Checks the invariants the executor and analyses rely on, since a deserialized graph didn't necessarily come from
+/// the parser.
+fn check_invariants(parsed: &ParseResult) -> Result<(), String> {
+    let head = match &parsed.head {
+        None => return Ok(()),
+        Some(head) => head,
+    };
+
+    let group_indices = parsed
+        .captures()
+        .iter()
+        .map(|capture| capture.index)
+        .collect::<HashSet<_>>();
+
+    let mut res = Ok(());
+    head.walk(&mut |node| {
+        if res.is_err() {
+            return;
+        }
+
+        res = match &node.val {
+            NodeVal::Or(branches) if branches.is_empty() => Err("or without any branches".to_string()),
+            NodeVal::RepetitionRange {
+                min, max: Some(max), ..
+            } if min > max => Err(format!("repetition range {{{},{}}} has a min above its max", min, max)),
+            NodeVal::Backreference(index) if !group_indices.contains(index) => {
+                Err(format!("backreference to unknown group {}", index))
+            }
+            _ => Ok(()),
+        };
+    });
+
+    res
+}
@@ -0,0 +1,331 @@
+use std::{iter::Peekable, str::Chars};
+
+use super::ParseError;
+
+/// The pattern dialect a [`super::Parser`] accepts.
+///
+/// Every dialect is translated into rustex syntax before parsing, so they all produce the same `Node` graph and run on
+/// the same executor.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Syntax {
+    /// The native rustex syntax.
+    #[default]
+    Rustex,
+
+    /// POSIX basic regular expressions (as used by `grep`), including the GNU `\|`, `\+` and `\?` extensions.
+    ///
+    /// Groups and intervals are written `\(...\)` and `\{m,n\}`; unescaped `(`, `)`, `{`, `}`, `|`, `+` and `?`
+    /// are literals. Backreferences (`\1`) are not supported.
+    PosixBre,
+
+    /// POSIX extended regular expressions (as used by `grep -E`).
+    PosixEre,
+
+    /// Shell globs: `*` and `?` match within a path segment, `**` matches across segments, `[!x]` negates a set and
+    /// `{a,b}` matches either alternative. Globs always match the whole input.
+    Glob,
+}
+
+/// A pattern translated into rustex syntax, along with enough bookkeeping to map positions back to the original.
+pub(super) struct Translated {
+    pub pattern: String,
+
+    // For each translated char, the original index where the token that produced it starts and the number of original
+    // chars consumed when it was emitted.
+    pub positions: Vec<(usize, usize)>,
+}
+
+/// Translates `input` from `syntax` into rustex syntax; errors carry the number of original chars consumed.
+///
+/// Bracket ranges like `[a-z]` are expanded into their members, so a range can cover at most `max_set_range` chars.
+pub(super) fn translate(syntax: Syntax, input: &str, max_set_range: u32) -> Result<Translated, (ParseError, usize)> {
+    let mut translator = Translator {
+        iter: input.chars().peekable(),
+        index: 0,
+        token_start: 0,
+        max_set_range,
+        out: Translated {
+            pattern: String::new(),
+            positions: vec![],
+        },
+    };
+
+    let res = match syntax {
+        Syntax::Rustex => {
+            while let Some(ch) = translator.next_token() {
+                translator.emit_char(ch);
+            }
+
+            Ok(())
+        }
+        Syntax::PosixBre => translator.translate_bre(),
+        Syntax::PosixEre => translator.translate_ere(),
+        Syntax::Glob => translator.translate_glob(),
+    };
+
+    match res {
+        Ok(()) => Ok(translator.out),
+        Err(err) => Err((err, translator.index)),
+    }
+}
+
+struct Translator<'a> {
+    iter: Peekable<Chars<'a>>,
+    index: usize,
+    token_start: usize,
+    max_set_range: u32,
+
+    out: Translated,
+}
+
+impl<'a> Translator<'a> {
+    const SPECIAL_CHARS: &'static [char] = &['(', ')', '{', '}', '[', ']', '|', '\\', '^', '$', '.', '*', '?', '+'];
+
+    fn translate_bre(&mut self) -> Result<(), ParseError> {
+        // Whether the previous token can't be repeated, in which case a '*' is a literal.
+        let mut at_expr_start = true;
+
+        while let Some(ch) = self.next_token() {
+            let was_at_expr_start = at_expr_start;
+            at_expr_start = false;
+
+            match ch {
+                '\\' => match self.next() {
+                    None => return Err(ParseError::MissingCharacterToEscape),
+                    Some(ch @ ('(' | '|')) => {
+                        self.emit(&ch.to_string());
+                        at_expr_start = true;
+                    }
+                    Some(ch @ (')' | '{' | '}' | '+' | '?')) => self.emit(&ch.to_string()),
+                    Some(ch @ ('.' | '*' | '[' | ']' | '^' | '$' | '\\')) => self.emit_literal(ch),
+                    Some(ch) => return Err(ParseError::UnexpectedCharErr(ch)),
+                },
+                '*' if was_at_expr_start => self.emit_literal(ch),
+                '^' if was_at_expr_start => {
+                    self.emit("^");
+                    at_expr_start = true;
+                }
+                '$' if self.at_bre_expr_end() => self.emit("$"),
+                '.' | '*' => self.emit(&ch.to_string()),
+                '[' => self.translate_bracket(false)?,
+                _ => self.emit_literal(ch),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn at_bre_expr_end(&mut self) -> bool {
+        // We can only peek one char ahead, so "\)" and "\|" are treated as the end of an expression by peeking the
+        // rest of the input on a clone.
+        let mut rest = self.iter.clone();
+        match rest.next() {
+            None => true,
+            Some('\\') => matches!(rest.next(), Some(')' | '|')),
+            Some(_) => false,
+        }
+    }
+
+    fn translate_ere(&mut self) -> Result<(), ParseError> {
+        while let Some(ch) = self.next_token() {
+            match ch {
+                '\\' => match self.next() {
+                    None => return Err(ParseError::MissingCharacterToEscape),
+                    Some(ch) if Self::is_special_char(&ch) => self.emit_literal(ch),
+                    Some(ch) => return Err(ParseError::UnexpectedCharErr(ch)),
+                },
+                '[' => self.translate_bracket(false)?,
+                _ => self.emit_char(ch),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn translate_glob(&mut self) -> Result<(), ParseError> {
+        let mut brace_depth = 0;
+        let mut at_segment_start = true;
+
+        self.emit("^");
+        while let Some(ch) = self.next_token() {
+            let was_at_segment_start = at_segment_start;
+            at_segment_start = ch == '/';
+
+            match ch {
+                '*' => {
+                    if self.iter.peek() != Some(&'*') {
+                        self.emit("[^/]*");
+                        continue;
+                    }
+
+                    self.next();
+
+                    // A "**/" segment matches zero or more whole directories.
+                    if was_at_segment_start && self.iter.peek() == Some(&'/') {
+                        self.next();
+                        self.emit("(?:.*/)?");
+                        at_segment_start = true;
+                    } else {
+                        self.emit(".*");
+                    }
+                }
+                '?' => self.emit("[^/]"),
+                '[' => self.translate_bracket(true)?,
+                '{' => {
+                    brace_depth += 1;
+                    self.emit("(?:");
+                }
+                ',' if brace_depth > 0 => self.emit("|"),
+                '}' if brace_depth > 0 => {
+                    brace_depth -= 1;
+                    self.emit(")");
+                }
+                '\\' => match self.next() {
+                    None => return Err(ParseError::MissingCharacterToEscape),
+                    Some(ch) => self.emit_literal(ch),
+                },
+                _ => self.emit_literal(ch),
+            }
+        }
+
+        if brace_depth > 0 {
+            return Err(ParseError::UnexpectedEndOfInput);
+        }
+
+        self.emit("$");
+
+        Ok(())
+    }
+
+    /// Translates a POSIX bracket expression (the opening '[' has already been consumed) into a rustex set.
+    ///
+    /// Backslashes are literal, a leading ']' is a member, ranges are expanded and `[:class:]`es are expanded to their
+    /// ASCII members.
+    fn translate_bracket(&mut self, is_glob: bool) -> Result<(), ParseError> {
+        self.emit("[");
+
+        match self.iter.peek() {
+            Some('^') => {
+                self.next();
+                self.emit("^");
+            }
+            Some('!') if is_glob => {
+                self.next();
+                self.emit("^");
+            }
+            _ => {}
+        }
+
+        let mut members = vec![];
+        let mut first = true;
+        loop {
+            let ch = match self.next() {
+                None => return Err(ParseError::UnterminatedCharSet),
+                Some(ch) => ch,
+            };
+
+            match ch {
+                ']' if !first => break,
+                '[' if self.iter.peek() == Some(&':') => {
+                    self.next();
+
+                    let mut class = String::new();
+                    loop {
+                        match self.next() {
+                            None => return Err(ParseError::UnterminatedCharSet),
+                            Some(':') if self.iter.peek() == Some(&']') => {
+                                self.next();
+                                break;
+                            }
+                            Some(ch) => class.push(ch),
+                        }
+                    }
+
+                    members.extend(Self::expand_class(&class)?);
+                }
+                '-' if !first && self.iter.peek().is_some_and(|next| *next != ']') => {
+                    let start = members.pop().unwrap_or('-');
+                    let end = self.next().expect("already peeked the end of the range");
+
+                    if end < start {
+                        return Err(ParseError::BadSetRange(start, end));
+                    }
+
+                    // Ranges are expanded, so they're capped to keep a pattern like [\x00-\u{10FFFF}] from blowing up.
+                    if end as u32 - start as u32 >= self.max_set_range {
+                        return Err(ParseError::SetRangeTooLarge(self.max_set_range));
+                    }
+
+                    members.extend(start..=end);
+                }
+                _ => members.push(ch),
+            }
+
+            first = false;
+        }
+
+        for member in members {
+            self.emit_literal(member);
+        }
+
+        self.emit("]");
+
+        Ok(())
+    }
+
+    fn expand_class(class: &str) -> Result<Vec<char>, ParseError> {
+        let pred: fn(&char) -> bool = match class {
+            "alpha" => char::is_ascii_alphabetic,
+            "digit" => char::is_ascii_digit,
+            "alnum" => char::is_ascii_alphanumeric,
+            "upper" => char::is_ascii_uppercase,
+            "lower" => char::is_ascii_lowercase,
+            "space" => |ch| ch.is_ascii_whitespace() || *ch == '\x0b',
+            "blank" => |ch| *ch == ' ' || *ch == '\t',
+            "punct" => char::is_ascii_punctuation,
+            "xdigit" => char::is_ascii_hexdigit,
+            "cntrl" => char::is_ascii_control,
+            "print" => |ch| ch.is_ascii_graphic() || *ch == ' ',
+            "graph" => char::is_ascii_graphic,
+            _ => return Err(ParseError::UnknownCharClass(class.into())),
+        };
+
+        Ok((0..=127u8).map(char::from).filter(pred).collect())
+    }
+
+    fn is_special_char(ch: &char) -> bool {
+        Self::SPECIAL_CHARS.contains(ch)
+    }
+
+    /// Consumes the first char of a new token.
+    fn next_token(&mut self) -> Option<char> {
+        self.token_start = self.index;
+        self.next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let ch = self.iter.next()?;
+        self.index += 1;
+
+        Some(ch)
+    }
+
+    fn emit(&mut self, str: &str) {
+        for ch in str.chars() {
+            self.emit_char(ch);
+        }
+    }
+
+    fn emit_char(&mut self, ch: char) {
+        self.out.pattern.push(ch);
+        self.out.positions.push((self.token_start, self.index));
+    }
+
+    fn emit_literal(&mut self, ch: char) {
+        if Self::is_special_char(&ch) {
+            self.emit_char('\\');
+        }
+
+        self.emit_char(ch);
+    }
+}
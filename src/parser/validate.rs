@@ -0,0 +1,119 @@
+use core::fmt;
+
+use super::{Node, NodeVal, ParseError, Span};
+
+/// Something suspicious about a pattern that still parses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseWarning {
+    pub kind: ParseWarningKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseWarningKind {
+    /// A modifier like `*` or `{m,n}` is applied to `^` or `$`, which can only match once.
+    QuantifiedAnchor,
+
+    /// A set lists the same member more than once.
+    DuplicateSetMember(char),
+
+    /// Input must be consumed after `$`, so the expression can never match.
+    ConsumingAfterEnd,
+
+    /// An alternation has an empty branch (like `a|` or `a||b`), which matches wherever the others don't.
+    EmptyAlternative,
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ParseWarningKind::QuantifiedAnchor => write!(f, "quantified anchor")?,
+            ParseWarningKind::DuplicateSetMember(ch) => write!(f, "duplicate set member '{}'", ch)?,
+            ParseWarningKind::ConsumingAfterEnd => write!(f, "expression after '$' can never match")?,
+            ParseWarningKind::EmptyAlternative => write!(f, "empty alternative")?,
+        }
+
+        write!(f, " at {}", self.span)
+    }
+}
+
+/// Checks a parsed pattern for impossible constructs (returned as errors) and suspicious ones (returned as warnings).
+pub(super) fn validate(head: &Node) -> Result<Vec<ParseWarning>, (ParseError, Span)> {
+    let mut warnings = vec![];
+    validate_chain(head, &mut warnings)?;
+
+    Ok(warnings)
+}
+
+fn validate_chain(head: &Node, warnings: &mut Vec<ParseWarning>) -> Result<(), (ParseError, Span)> {
+    let mut after_end = false;
+
+    for node in head.iter() {
+        if after_end && !node.val.matches_empty() {
+            warnings.push(ParseWarning {
+                kind: ParseWarningKind::ConsumingAfterEnd,
+                span: node.span,
+            });
+
+            // One warning per chain is enough.
+            after_end = false;
+        }
+
+        match &node.val {
+            NodeVal::End => after_end = true,
+            NodeVal::Group { group, .. }
+                if group.iter().last().is_some_and(|last| matches!(last.val, NodeVal::End)) =>
+            {
+                after_end = true
+            }
+            NodeVal::RepetitionRange {
+                min, max: Some(max), ..
+            } if max < min => {
+                return Err((ParseError::InvalidRepetitionRange { min: *min, max: *max }, node.span));
+            }
+            _ => {}
+        }
+
+        match &node.val {
+            NodeVal::ZeroOrMore { node: inner, .. }
+            | NodeVal::OneOrMore { node: inner, .. }
+            | NodeVal::Optional(inner)
+            | NodeVal::RepetitionRange { node: inner, .. }
+                if is_anchor_chain(inner) =>
+            {
+                warnings.push(ParseWarning {
+                    kind: ParseWarningKind::QuantifiedAnchor,
+                    span: node.span,
+                });
+            }
+            NodeVal::Or(branches) => {
+                for branch in branches.iter().filter(|branch| is_empty_chain(branch)) {
+                    warnings.push(ParseWarning {
+                        kind: ParseWarningKind::EmptyAlternative,
+                        span: branch.span,
+                    });
+                }
+            }
+            _ => {}
+        }
+
+        for child in node.val.children() {
+            validate_chain(child, warnings)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether a chain is the placeholder the parser creates for an empty branch.
+fn is_empty_chain(head: &Node) -> bool {
+    head.next.is_none() && matches!(&head.val, NodeVal::Word(word) if word.is_empty())
+}
+
+fn is_anchor_chain(head: &Node) -> bool {
+    head.iter().all(|node| match &node.val {
+        NodeVal::Start | NodeVal::End => true,
+        NodeVal::Group { group, .. } => is_anchor_chain(group),
+        _ => false,
+    })
+}
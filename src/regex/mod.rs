@@ -0,0 +1,385 @@
+use core::fmt;
+use std::{borrow::Cow, ops::Range, sync::Arc};
+
+use crate::{
+    analysis::{self, Literals},
+    executor::{ExecIter, ExecResult, Executor, Haystack, MatchSemantics, OverlappingIter, SearchMode},
+    optimizer,
+    parser::{Capture, ParseResult, Parser, PositionedParseError, Syntax},
+};
+
+/// A compiled pattern that can be matched against any number of inputs.
+///
+/// Parsing, optimizing and analyzing the pattern happens once up front. A `Regex` is cheap to clone and can be shared
+/// across threads.
+#[derive(Clone)]
+pub struct Regex {
+    inner: Arc<RegexInner>,
+}
+
+struct RegexInner {
+    pattern: String,
+    parsed: ParseResult,
+    literals: Literals,
+    captures: Vec<Capture>,
+    executor: Executor,
+}
+
+impl Regex {
+    /// Compiles a pattern in rustex syntax with the default limits.
+    pub fn new(pattern: &str) -> Result<Self, PositionedParseError> {
+        RegexBuilder::new(pattern).build()
+    }
+
+    /// Returns the pattern this regex was compiled from.
+    pub fn as_str(&self) -> &str {
+        &self.inner.pattern
+    }
+
+    /// Returns the compiled (and possibly optimized) pattern.
+    pub fn parse_result(&self) -> &ParseResult {
+        &self.inner.parsed
+    }
+
+    /// Returns the number of groups in a match, including group 0 (the whole match).
+    pub fn captures_len(&self) -> usize {
+        self.inner.captures.len() + 1
+    }
+
+    /// Lists the name of every group in a match by index (group 0 and unnamed groups have no name).
+    pub fn capture_names(&self) -> impl Iterator<Item = Option<&str>> {
+        std::iter::once(None).chain(self.inner.captures.iter().map(|capture| capture.name.as_deref()))
+    }
+
+    /// Returns whether there is a match anywhere in `input`.
+    pub fn is_match(&self, input: &str) -> bool {
+        self.exec(input, 0).is_some()
+    }
+
+    /// Finds a match in `input`.
+    pub fn find<'input>(&self, input: &'input str) -> Option<Match<'input>> {
+        self.find_at(input, 0)
+    }
+
+    /// Finds a match in `input` starting the search at byte offset `start`.
+    ///
+    /// The rest of `input` is still taken into account, so `^` never matches after 0. There's no match if `start` is past
+    /// the end of `input`.
+    pub fn find_at<'input>(&self, input: &'input str, start: usize) -> Option<Match<'input>> {
+        self.exec(input, start).map(|res| Match::new(input, res.start, res.end))
+    }
+
+    /// Finds a match in `input` along with the spans of its groups.
+    pub fn captures<'input>(&self, input: &'input str) -> Option<Captures<'input>> {
+        self.captures_at(input, 0)
+    }
+
+    /// Finds a match in `input` along with the spans of its groups, starting the search at byte offset `start`.
+    pub fn captures_at<'input>(&self, input: &'input str, start: usize) -> Option<Captures<'input>> {
+        self.exec(input, start).map(|res| Captures {
+            input,
+            res,
+            len: self.captures_len(),
+        })
+    }
+
+    /// Iterates over the successive non-overlapping matches in `input`.
+    pub fn find_iter<'r, 'input>(&'r self, input: &'input str) -> Matches<'r, 'input> {
+        Matches {
+            iter: self.exec_iter(input),
+            input,
+        }
+    }
+
+    /// Iterates over the groups of the successive non-overlapping matches in `input`.
+    pub fn captures_iter<'r, 'input>(&'r self, input: &'input str) -> CaptureMatches<'r, 'input> {
+        CaptureMatches {
+            iter: self.exec_iter(input),
+            input,
+            len: self.captures_len(),
+        }
+    }
+
+    /// Iterates over the matches starting at every position in `input`, including ones that overlap.
+    pub fn find_overlapping<'r, 'input>(&'r self, input: &'input str) -> OverlappingMatches<'r, 'input> {
+        OverlappingMatches {
+            iter: OverlappingIter::new(
+                self.inner.executor,
+                &self.inner.parsed,
+                Cow::Borrowed(&self.inner.literals),
+                Haystack::Str(input),
+            ),
+            input,
+        }
+    }
+
+    fn exec_iter<'r, 'input>(&'r self, input: &'input str) -> ExecIter<'r, 'input> {
+        ExecIter::new(
+            self.inner.executor,
+            &self.inner.parsed,
+            Cow::Borrowed(&self.inner.literals),
+            Haystack::Str(input),
+        )
+    }
+
+    fn exec(&self, input: &str, start: usize) -> Option<ExecResult> {
+        // Executing a compiled pattern can't fail.
+        self.inner
+            .executor
+            .exec_with_literals(&self.inner.parsed, &self.inner.literals, Haystack::Str(input), start)
+            .ok()
+            .flatten()
+    }
+}
+
+impl fmt::Debug for Regex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Regex").field(&self.inner.pattern).finish()
+    }
+}
+
+impl fmt::Display for Regex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.inner.pattern)
+    }
+}
+
+/// Configures how a [`Regex`] is compiled.
+pub struct RegexBuilder {
+    pattern: String,
+    syntax: Syntax,
+    max_nesting: usize,
+    max_repetition: u32,
+    max_nodes: usize,
+    max_set_range: u32,
+    optimize: bool,
+    semantics: MatchSemantics,
+    mode: SearchMode,
+}
+
+impl RegexBuilder {
+    pub fn new(pattern: &str) -> Self {
+        RegexBuilder {
+            pattern: pattern.to_string(),
+            syntax: Syntax::default(),
+            max_nesting: Parser::DEFAULT_MAX_NESTING,
+            max_repetition: Parser::DEFAULT_MAX_REPETITION,
+            max_nodes: Parser::DEFAULT_MAX_NODES,
+            max_set_range: Parser::DEFAULT_MAX_SET_RANGE,
+            optimize: true,
+            semantics: MatchSemantics::default(),
+            mode: SearchMode::default(),
+        }
+    }
+
+    /// Sets the dialect the pattern is written in (rustex syntax by default).
+    pub fn syntax(mut self, syntax: Syntax) -> Self {
+        self.syntax = syntax;
+        self
+    }
+
+    /// See [`Parser::max_nesting`].
+    pub fn max_nesting(mut self, max_nesting: usize) -> Self {
+        self.max_nesting = max_nesting;
+        self
+    }
+
+    /// See [`Parser::max_repetition`].
+    pub fn max_repetition(mut self, max_repetition: u32) -> Self {
+        self.max_repetition = max_repetition;
+        self
+    }
+
+    /// See [`Parser::max_nodes`].
+    pub fn max_nodes(mut self, max_nodes: usize) -> Self {
+        self.max_nodes = max_nodes;
+        self
+    }
+
+    /// See [`Parser::max_set_range`].
+    pub fn max_set_range(mut self, max_set_range: u32) -> Self {
+        self.max_set_range = max_set_range;
+        self
+    }
+
+    /// Sets whether the pattern is run through the [`optimizer`] (on by default).
+    pub fn optimize(mut self, optimize: bool) -> Self {
+        self.optimize = optimize;
+        self
+    }
+
+    /// Sets how a match is picked when several start at the same position (leftmost-first by default).
+    pub fn semantics(mut self, semantics: MatchSemantics) -> Self {
+        self.semantics = semantics;
+        self
+    }
+
+    /// Sets where a match can start and end relative to the search (unanchored by default).
+    pub fn mode(mut self, mode: SearchMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn build(&self) -> Result<Regex, PositionedParseError> {
+        let parsed = Parser::with_syntax(self.syntax)
+            .max_nesting(self.max_nesting)
+            .max_repetition(self.max_repetition)
+            .max_nodes(self.max_nodes)
+            .max_set_range(self.max_set_range)
+            .parse_str(&self.pattern)
+            .map_err(|err| err.into_owned())?;
+
+        let parsed = match self.optimize {
+            true => optimizer::optimize(&parsed),
+            false => parsed,
+        };
+
+        Ok(Regex {
+            inner: Arc::new(RegexInner {
+                pattern: self.pattern.clone(),
+                literals: analysis::extract_literals(&parsed),
+                captures: parsed.captures(),
+                executor: Executor::new().semantics(self.semantics).mode(self.mode),
+                parsed,
+            }),
+        })
+    }
+}
+
+/// A span of an input matched by a [`Regex`] (or one of its groups).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match<'input> {
+    input: &'input str,
+    start: usize,
+    end: usize,
+}
+
+impl<'input> Match<'input> {
+    fn new(input: &'input str, start: usize, end: usize) -> Self {
+        Match { input, start, end }
+    }
+
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// Returns the (exclusive) end of the match.
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn as_str(&self) -> &'input str {
+        &self.input[self.range()]
+    }
+}
+
+/// The groups of a single match of a [`Regex`].
+#[derive(Debug, Clone)]
+pub struct Captures<'input> {
+    input: &'input str,
+    res: ExecResult,
+    len: usize,
+}
+
+impl<'input> Captures<'input> {
+    /// Returns group `index` (group 0 is the whole match), if it participated in the match.
+    pub fn get(&self, index: usize) -> Option<Match<'input>> {
+        self.res
+            .group(index)
+            .map(|(start, end)| Match::new(self.input, start, end))
+    }
+
+    /// Returns the group with the given name, if it participated in the match.
+    pub fn name(&self, name: &str) -> Option<Match<'input>> {
+        self.res
+            .named_group(name)
+            .map(|(start, end)| Match::new(self.input, start, end))
+    }
+
+    /// Returns the number of groups in the pattern, including group 0 (whether or not they participated).
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Iterates over every group by index, with `None` for groups that didn't participate.
+    pub fn iter(&self) -> impl Iterator<Item = Option<Match<'input>>> + '_ {
+        (0..self.len).map(|index| self.get(index))
+    }
+
+    /// Returns the raw executor result this was built from.
+    pub fn exec_result(&self) -> &ExecResult {
+        &self.res
+    }
+}
+
+/// An iterator over the non-overlapping matches of a [`Regex`], created by [`Regex::find_iter`].
+pub struct Matches<'r, 'input> {
+    iter: ExecIter<'r, 'input>,
+    input: &'input str,
+}
+
+impl<'input> Iterator for Matches<'_, 'input> {
+    type Item = Match<'input>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|res| Match::new(self.input, res.start, res.end))
+    }
+}
+
+/// An iterator over the groups of the non-overlapping matches of a [`Regex`], created by [`Regex::captures_iter`].
+pub struct CaptureMatches<'r, 'input> {
+    iter: ExecIter<'r, 'input>,
+    input: &'input str,
+    len: usize,
+}
+
+impl<'input> Iterator for CaptureMatches<'_, 'input> {
+    type Item = Captures<'input>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|res| Captures {
+            input: self.input,
+            res,
+            len: self.len,
+        })
+    }
+}
+
+/// An iterator over the (possibly overlapping) matches of a [`Regex`] at every start, created by
+/// [`Regex::find_overlapping`].
+pub struct OverlappingMatches<'r, 'input> {
+    iter: OverlappingIter<'r, 'input>,
+    input: &'input str,
+}
+
+impl OverlappingMatches<'_, '_> {
+    /// See [`OverlappingIter::all_ends`].
+    pub fn all_ends(mut self, all_ends: bool) -> Self {
+        self.iter = self.iter.all_ends(all_ends);
+        self
+    }
+}
+
+impl<'input> Iterator for OverlappingMatches<'_, 'input> {
+    type Item = Match<'input>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|res| Match::new(self.input, res.start, res.end))
+    }
+}
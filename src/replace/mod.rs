@@ -1,6 +1,4 @@
-use tracing::debug;
-
-use crate::executor;
+use crate::{executor, trace::debug};
 
 #[derive(Debug)]
 pub struct ReplaceSpec {
@@ -10,7 +8,8 @@ pub struct ReplaceSpec {
 #[derive(Debug)]
 pub enum ReplaceSpecNodeValue {
     String(String),
-    GroupNum(String),
+    GroupNum(usize),
+    GroupName(String),
 }
 
 impl<'a> From<&'a str> for ReplaceSpec {
@@ -41,11 +40,28 @@ impl ReplaceSpec {
                 spec.parts.push(ReplaceSpecNodeValue::String(word));
             }
 
+            // Named groups are referenced as ${name}.
+            if chars.peek() == Some(&'{') {
+                chars.next();
+
+                let mut group_name = String::new();
+                for next in chars.by_ref() {
+                    if next == '}' {
+                        break;
+                    }
+
+                    group_name.push(next);
+                }
+
+                spec.parts.push(ReplaceSpecNodeValue::GroupName(group_name));
+                continue;
+            }
+
             let group_num = {
                 let mut group_num = String::new();
                 while let Some(next) = chars.peek() {
                     match *next {
-                        '1'..='9' => {
+                        '0'..='9' => {
                             group_num.push(*next);
                             chars.next();
                         }
@@ -55,7 +71,12 @@ impl ReplaceSpec {
 
                 group_num
             };
-            spec.parts.push(ReplaceSpecNodeValue::GroupNum(group_num));
+
+            // A '$' that isn't followed by a group reference is just a '$'.
+            match group_num.parse::<usize>() {
+                Ok(group_num) => spec.parts.push(ReplaceSpecNodeValue::GroupNum(group_num)),
+                Err(_) => curr_word = Some(format!("${}", group_num)),
+            }
         }
 
         if let Some(word) = curr_word {
@@ -65,20 +86,32 @@ impl ReplaceSpec {
         spec
     }
 
-    #[tracing::instrument]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
     pub fn perform_replace(&self, input: &str, res: &executor::ExecResult) -> Option<String> {
+        // Groups of a match in a str always start and end on char boundaries.
+        self.perform_replace_bytes(input.as_bytes(), res)
+            .map(|replaced| String::from_utf8(replaced).expect("replacement should be valid UTF-8"))
+    }
+
+    /// Like [`ReplaceSpec::perform_replace`], but for a match found by [`executor::Executor::exec_bytes`].
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn perform_replace_bytes(&self, input: &[u8], res: &executor::ExecResult) -> Option<Vec<u8>> {
         debug!("performing replace");
 
         if self.parts.is_empty() {
             return None;
         }
 
-        let replaced = self.parts.iter().fold(String::new(), |mut acc, part| {
+        let replaced = self.parts.iter().fold(Vec::new(), |mut acc, part| {
             match part {
-                ReplaceSpecNodeValue::String(str) => acc.push_str(str),
-                ReplaceSpecNodeValue::GroupNum(group_name) => match res.groups.get(group_name) {
-                    None => acc.push_str(&format!("${}", group_name)),
-                    Some(val) => acc.push_str(&input[val.0..=val.1]),
+                ReplaceSpecNodeValue::String(str) => acc.extend_from_slice(str.as_bytes()),
+                ReplaceSpecNodeValue::GroupNum(group_num) => match res.group(*group_num) {
+                    None => acc.extend_from_slice(format!("${}", group_num).as_bytes()),
+                    Some(val) => acc.extend_from_slice(&input[val.0..val.1]),
+                },
+                ReplaceSpecNodeValue::GroupName(group_name) => match res.named_group(group_name) {
+                    None => acc.extend_from_slice(format!("${{{}}}", group_name).as_bytes()),
+                    Some(val) => acc.extend_from_slice(&input[val.0..val.1]),
                 },
             }
 
@@ -0,0 +1,12 @@
+//! Logging macros that compile to nothing when the `tracing` feature is disabled.
+
+#[cfg(feature = "tracing")]
+pub(crate) use tracing::debug;
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! debug {
+    ($($arg:tt)*) => {};
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) use debug;
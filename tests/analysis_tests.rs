@@ -0,0 +1,70 @@
+use rustex::{analysis, parser::Parser};
+
+fn analyze_all(patterns: &[&'static str]) -> Vec<(&'static str, Vec<String>)> {
+    let parser = Parser::new();
+
+    patterns
+        .iter()
+        .map(|pattern| {
+            let parsed = parser.parse_str(pattern).expect("failed to parse");
+
+            (
+                *pattern,
+                analysis::analyze(&parsed).iter().map(ToString::to_string).collect(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_nested_quantifiers() {
+    insta::assert_debug_snapshot!(analyze_all(&[
+        "(a+)+b",
+        "(a*b?)*c",
+        "([ab]+ ?)+$",
+        "(a+){2,5}",
+        "(a+b)+",
+        "(ab)+",
+    ]));
+}
+
+#[test]
+fn test_overlapping_alternations() {
+    insta::assert_debug_snapshot!(analyze_all(&[
+        "(a|a)*b",
+        "([abc]|b)+",
+        "(a|aa)*",
+        "(a|ab)*",
+        "(ab|ac)*",
+        "(x|y|x)+",
+    ]));
+}
+
+#[test]
+fn test_adjacent_quantifiers() {
+    insta::assert_debug_snapshot!(analyze_all(&["a+[ab]+", ".*x?.*", "a+b+", "a+b.*"]));
+}
+
+#[test]
+fn test_extract_literals() {
+    let parser = Parser::new();
+
+    let literals = [
+        "ERROR.* timeout",
+        "^(foo|bar)baz$",
+        "colou?r",
+        "a[bc]d",
+        "(ab){3}x+",
+        ".*(needle).*",
+        "x*|y",
+    ]
+    .iter()
+    .map(|pattern| {
+        let parsed = parser.parse_str(pattern).expect("failed to parse");
+
+        (*pattern, analysis::extract_literals(&parsed))
+    })
+    .collect::<Vec<_>>();
+
+    insta::assert_debug_snapshot!(literals);
+}
@@ -1,8 +1,10 @@
+#![cfg(feature = "async")]
+
 use std::sync;
 
 use rustex::{
     executor::{self, ExecResult},
-    parser,
+    optimizer, parser,
 };
 
 static INIT: sync::Once = std::sync::Once::new();
@@ -21,15 +23,14 @@ impl core::fmt::Debug for FormattableExecResult<'_, '_> {
         f.write_fmt(format_args!("p: '{}'\n", self.pattern))?;
         f.write_fmt(format_args!("i: '{}'\n", self.input))?;
 
-        f.write_fmt(format_args!(
-            "    {}^{}^",
-            " ".repeat(self.result.start as usize),
-            " ".repeat(if self.result.end > 0 {
-                (self.result.end - self.result.start) - 1
-            } else {
-                0
-            } as usize),
-        ))
+        // Point at the first and last matched chars (or between chars for an empty match).
+        let marker = match self.result.end - self.result.start {
+            0 => "|".to_string(),
+            1 => "^".to_string(),
+            n => format!("^{}^", " ".repeat(n - 2)),
+        };
+
+        f.write_fmt(format_args!("    {}{}", " ".repeat(self.result.start), marker))
     }
 }
 
@@ -39,14 +40,22 @@ async fn run_test<'p, 'i>(pattern: &'p str, input: &'i str) -> FormattableExecRe
     });
 
     let parser = parser::Parser::new();
-    let mut executor = executor::Executor::new();
+    let executor = executor::Executor::new();
 
+    let parsed = parser.parse_str(pattern).expect("should parse");
     let result = executor
-        .exec(&parser.parse_str(pattern).expect("should parse"), input)
+        .exec(&parsed, input)
         .await
         .expect("should exec")
         .expect("expected exec result");
 
+    // The optimized graph has to match exactly the same way.
+    let optimized_result = executor
+        .exec(&optimizer::optimize(&parsed), input)
+        .await
+        .expect("should exec");
+    assert_eq!(format!("{:?}", Some(&result)), format!("{:?}", optimized_result));
+
     FormattableExecResult { result, pattern, input }
 }
 
@@ -136,3 +145,248 @@ async fn test_optional_match_branch_group() {
 
     insta::assert_debug_snapshot!(result);
 }
+
+#[tokio::test]
+async fn test_group_lookups() {
+    let result = run_test("(?<one>h)(e)(?:l)(?<two>l)", "hello").await.result;
+
+    assert_eq!(result.group(0), Some((0, 4)));
+    assert_eq!(result.group(2), Some((1, 2)));
+    assert_eq!(result.group(3), result.named_group("two"));
+    assert_eq!(result.named_group("one"), Some((0, 1)));
+    assert_eq!(result.group(4), None);
+}
+
+#[tokio::test]
+async fn test_backreference() {
+    let result = run_test("(?P<quote>[\"'])(?'body'[^\"']+)(?P=quote)", "'hi' \"there\"").await;
+
+    insta::assert_debug_snapshot!(result);
+}
+
+#[tokio::test]
+async fn test_glob() {
+    let glob = parser::Parser::with_syntax(parser::Syntax::Glob)
+        .parse_str("src/**/*.{rs,toml}")
+        .expect("should parse");
+
+    for (input, should_match) in [
+        ("src/main.rs", true),
+        ("src/parser/mod.rs", true),
+        ("src/a/b/Cargo.toml", true),
+        ("src/main.rsx", false),
+        ("tests/main.rs", false),
+    ] {
+        let res = executor::Executor::new().exec(&glob, input).await.expect("should exec");
+
+        assert_eq!(res.is_some(), should_match, "input: {}", input);
+    }
+}
+
+#[tokio::test]
+async fn test_exact_repetition() {
+    let result = run_test("ba{2}", "baaa").await.result;
+
+    assert_eq!((result.start, result.end), (0, 3));
+}
+
+#[tokio::test]
+async fn test_prefilter() {
+    let result = run_test("ERROR.* timeout", "INFO ok\nERROR db timeout").await;
+
+    insta::assert_debug_snapshot!(result);
+
+    let parser = parser::Parser::new();
+    let result = executor::Executor::new()
+        .exec(
+            &parser.parse_str("ERROR.* timeout").expect("should parse"),
+            "ERROR db slow",
+        )
+        .await
+        .expect("should exec");
+
+    assert!(result.is_none());
+}
+
+#[tokio::test]
+async fn test_empty_alternative() {
+    let result = run_test("colo(u|)r (?<suffix>s|)$", "color ").await;
+
+    insta::assert_debug_snapshot!(result);
+}
+
+#[tokio::test]
+async fn test_unanchored_set() {
+    let result = run_test("[0123456789]x", "ab1x").await;
+
+    insta::assert_debug_snapshot!(result);
+}
+
+#[tokio::test]
+async fn test_unanchored_group() {
+    let result = run_test("(?<bs>b+)c", "abbabbbc").await;
+
+    insta::assert_debug_snapshot!(result);
+}
+
+#[tokio::test]
+async fn test_unanchored_or() {
+    let result = run_test("(cat|dog)s", "hot dogs").await;
+
+    insta::assert_debug_snapshot!(result);
+}
+
+#[tokio::test]
+async fn test_zero_length_matches() {
+    for (pattern, input, expected) in [
+        ("^$", "", (0, 0)),
+        ("x*", "abc", (0, 0)),
+        ("a?", "", (0, 0)),
+        ("()", "abc", (0, 0)),
+        ("(a|)*b", "b", (0, 1)),
+    ] {
+        let result = run_test(pattern, input).await.result;
+
+        assert_eq!((result.start, result.end), expected, "pattern: {}", pattern);
+    }
+}
+
+#[tokio::test]
+async fn test_multibyte_input() {
+    // Every span is in bytes, so `é` (2 bytes) and `ß` (2 bytes) count double.
+    for (pattern, input, expected) in [
+        ("né", "canné!", (3, 6)),
+        ("a.b", "xaéb", (1, 5)),
+        ("^é+$", "éé", (0, 4)),
+        ("[éè]x", "aèx", (1, 4)),
+        ("[^a]b", "aéb", (1, 4)),
+        ("ü?x", "üx", (0, 3)),
+        ("aé*", "aééb", (0, 5)),
+        ("é+", "xéé", (1, 5)),
+        ("é{2}", "éééé", (0, 4)),
+        ("(?<g>ß)c", "aßc", (1, 4)),
+        ("ö|ä", "xä", (1, 3)),
+        ("(?<v>é)(?P=v)", "aéé", (1, 5)),
+        (".*?ñ", "añb", (0, 3)),
+        ("x*", "é", (0, 0)),
+    ] {
+        let result = run_test(pattern, input).await.result;
+
+        assert_eq!((result.start, result.end), expected, "pattern: {}", pattern);
+        assert!(input.get(result.start..result.end).is_some(), "pattern: {}", pattern);
+    }
+}
+
+#[test]
+fn test_exec_sync() {
+    let parsed = parser::Parser::new()
+        .parse_str("(?<word>b[aeiou]r)")
+        .expect("should parse");
+
+    let result = executor::Executor::new()
+        .exec_sync(&parsed, "foo bar baz")
+        .expect("should exec")
+        .expect("expected exec result");
+
+    assert_eq!((result.start, result.end), (4, 7));
+    assert_eq!(result.named_group("word"), Some((4, 7)));
+}
+
+#[test]
+fn test_exec_at_past_end() {
+    let parsed = parser::Parser::new().parse_str("a*").expect("should parse");
+    let executor = executor::Executor::new();
+
+    let result = executor.exec_at(&parsed, "aa", 2).expect("should exec");
+    assert_eq!(result.map(|res| (res.start, res.end)), Some((2, 2)));
+
+    assert!(executor.exec_at(&parsed, "aa", 3).expect("should exec").is_none());
+}
+
+#[test]
+fn test_exec_iter() {
+    let parsed = parser::Parser::new().parse_str("o+").expect("should parse");
+
+    let spans = executor::Executor::new()
+        .exec_iter(&parsed, "foo bar boooz")
+        .map(|res| (res.start, res.end))
+        .collect::<Vec<_>>();
+
+    assert_eq!(spans, vec![(1, 3), (9, 12)]);
+}
+
+#[test]
+fn test_exec_bytes() {
+    // `\xHH` is a raw byte, while chars still match their UTF-8 encoding.
+    for (pattern, input, invalid_utf8, expected) in [
+        ("b.r", &b"\xFFbar"[..], false, Some((1, 4))),
+        ("\\xFF+", b"a\xFF\xFFb", false, Some((1, 3))),
+        ("a\\xE9", "aé".as_bytes(), false, None),
+        ("aé", "aé".as_bytes(), false, Some((0, 3))),
+        ("a.b", b"a\xFFb", false, None),
+        ("a.b", b"a\xFFb", true, Some((0, 3))),
+        ("[^x]b", b"\xC3b", false, None),
+        ("[^x]b", b"\xC3b", true, Some((0, 2))),
+        ("^.*$", b"ab\xFF", true, Some((0, 3))),
+    ] {
+        let parsed = parser::Parser::new().parse_str(pattern).expect("should parse");
+
+        let result = executor::Executor::new()
+            .match_invalid_utf8(invalid_utf8)
+            .exec_bytes(&parsed, input)
+            .expect("should exec");
+
+        assert_eq!(result.map(|res| (res.start, res.end)), expected, "pattern: {}", pattern);
+    }
+}
+
+#[test]
+fn test_exec_bytes_at() {
+    let parsed = parser::Parser::new().parse_str("\\xFF").expect("should parse");
+    let executor = executor::Executor::new();
+
+    let result = executor.exec_bytes_at(&parsed, b"\xFFa\xFF", 1).expect("should exec");
+    assert_eq!(result.map(|res| (res.start, res.end)), Some((2, 3)));
+
+    assert!(executor
+        .exec_bytes_at(&parsed, b"\xFF", 2)
+        .expect("should exec")
+        .is_none());
+}
+
+#[test]
+fn test_exec_bytes_iter() {
+    let parsed = parser::Parser::new().parse_str("a*").expect("should parse");
+
+    let spans = executor::Executor::new()
+        .exec_bytes_iter(&parsed, b"a\xFF\xFFaa")
+        .map(|res| (res.start, res.end))
+        .collect::<Vec<_>>();
+
+    assert_eq!(spans, vec![(0, 1), (2, 2), (3, 5)]);
+}
+
+#[test]
+fn test_byte_escape_in_str() {
+    let parsed = parser::Parser::new().parse_str("caf\\xE9").expect("should parse");
+
+    let result = executor::Executor::new()
+        .exec_sync(&parsed, "un café")
+        .expect("should exec")
+        .expect("expected exec result");
+
+    assert_eq!((result.start, result.end), (3, 8));
+}
+
+#[test]
+fn test_exec_overlapping() {
+    let parsed = parser::Parser::new().parse_str("o+").expect("should parse");
+
+    let spans = executor::Executor::new()
+        .exec_overlapping(&parsed, "foo boo")
+        .all_ends(true)
+        .map(|res| (res.start, res.end))
+        .collect::<Vec<_>>();
+
+    assert_eq!(spans, vec![(1, 2), (1, 3), (2, 3), (5, 6), (5, 7), (6, 7)]);
+}
@@ -0,0 +1,63 @@
+use rustex::{
+    export::{export, Flavor},
+    parser::Parser,
+    regex::Regex,
+};
+
+fn run_test(pattern: &str) -> String {
+    let parsed = Parser::new().parse_str(pattern).expect("should parse");
+
+    [Flavor::Pcre, Flavor::EcmaScript, Flavor::PosixEre]
+        .into_iter()
+        .map(|flavor| match export(&parsed, flavor) {
+            Ok(exported) => format!("{:?}: {}", flavor, exported),
+            Err(err) => format!("{:?}: error: {}", flavor, err),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn test_export_literals() {
+    insta::assert_snapshot!(run_test("^a\\.b/c\\{\\}d$"));
+}
+
+#[test]
+fn test_export_sets() {
+    insta::assert_snapshot!(run_test("[a\\]\\^-][^\\\\b][\\^][\\^-]"));
+}
+
+#[test]
+fn test_export_quantifiers() {
+    insta::assert_snapshot!(run_test("ab*c+d?e{2,3}f{4,}(gh)*"));
+}
+
+#[test]
+fn test_export_or() {
+    insta::assert_snapshot!(run_test("(a|b)c|d"));
+}
+
+#[test]
+fn test_export_named_group() {
+    insta::assert_snapshot!(run_test("(?<word>[abc]+) (?P=word)"));
+}
+
+#[test]
+fn test_export_backreference_before_digit() {
+    insta::assert_snapshot!(run_test("(?<x>a)(?P=x)0"));
+}
+
+#[test]
+fn test_export_backreference_to_unset_group() {
+    // rustex never matches a backreference to a group that didn't participate, but ECMAScript matches it as empty.
+    let regex = Regex::new("(?<g>a)?(?P=g)b").expect("should compile");
+    assert!(!regex.is_match("b"));
+    assert!(regex.is_match("aab"));
+
+    insta::assert_snapshot!(run_test("(?<g>a)?(?P=g)b"));
+}
+
+#[test]
+fn test_export_lazy() {
+    insta::assert_snapshot!(run_test("(a.*?)b"));
+}
@@ -0,0 +1,67 @@
+use rustex::{executor, optimizer, parser::Parser};
+
+fn optimize_all(patterns: &[&'static str]) -> Vec<(&'static str, String)> {
+    let parser = Parser::new();
+
+    patterns
+        .iter()
+        .map(|pattern| {
+            let parsed = parser.parse_str(pattern).expect("failed to parse");
+
+            (*pattern, format!("{:?}", optimizer::optimize(&parsed)))
+        })
+        .collect()
+}
+
+#[test]
+fn test_optimize_literals() {
+    insta::assert_debug_snapshot!(optimize_all(&["hello world", "a[b]c", "ab*[c]d", "[^a]", "(a)[b]"]));
+}
+
+#[test]
+fn test_optimize_alternations() {
+    insta::assert_debug_snapshot!(optimize_all(&[
+        "a|b|c",
+        "a|[bc]|de|f",
+        "abc|abd|abe",
+        "foobar|foo",
+        "foo|foobar",
+        "cat|car|dog|do",
+        "(a)|b|c",
+        "x(ab|ac)y",
+    ]));
+}
+
+#[test]
+fn test_optimized_matches_are_equivalent() {
+    let parser = Parser::new();
+    let executor = executor::Executor::new();
+
+    let cases = [
+        ("^(a|b|c)d", vec!["bd", "cd", "dd"]),
+        ("^(abc|abd|abe)x", vec!["abdx", "abx", "abex"]),
+        ("^(foobar|foo)", vec!["foobar", "foob", "fo"]),
+        ("^(foo|foobar)$", vec!["foobar", "foo", "foob"]),
+        ("^(cat|car|dog|do)s?$", vec!["cars", "do", "dogs", "ca"]),
+        ("^x(?:ab|ac)y$", vec!["xaby", "xacy", "xady"]),
+        ("^a[b]c(?<d>[d])$", vec!["abcd", "abce"]),
+    ];
+
+    for (pattern, inputs) in cases {
+        let parsed = parser.parse_str(pattern).expect("failed to parse");
+        let optimized = optimizer::optimize(&parsed);
+
+        for input in inputs {
+            let expected = executor.exec_sync(&parsed, input).expect("should exec");
+            let actual = executor.exec_sync(&optimized, input).expect("should exec");
+
+            assert_eq!(
+                format!("{:?}", expected),
+                format!("{:?}", actual),
+                "'{}' against '{}'",
+                pattern,
+                input
+            );
+        }
+    }
+}
@@ -38,6 +38,17 @@ fn test_parse_escaped() {
     insta::assert_debug_snapshot!(parsed);
 }
 
+#[test]
+fn test_parse_byte_escapes() {
+    let parser = Parser::new();
+
+    let parsed = parser
+        .parse_str("caf\\xE9 \\x41\\xFF[\\x41b]")
+        .expect("failed to parse");
+
+    insta::assert_debug_snapshot!(parsed);
+}
+
 #[test]
 fn test_parse_or() {
     let parser = Parser::new();
@@ -47,6 +58,24 @@ fn test_parse_or() {
     insta::assert_debug_snapshot!(parsed);
 }
 
+#[test]
+fn test_parse_or_branches() {
+    let parser = Parser::new();
+
+    let parsed = parser.parse_str("a|b|c").expect("failed to parse");
+
+    insta::assert_debug_snapshot!(parsed);
+}
+
+#[test]
+fn test_parse_or_anchors() {
+    let parser = Parser::new();
+
+    let parsed = parser.parse_str("^a|b$").expect("failed to parse");
+
+    insta::assert_debug_snapshot!(parsed);
+}
+
 #[test]
 fn test_parse_repetition_range() {
     let parser = Parser::new();
@@ -73,3 +102,260 @@ fn test_unexpected_char_err() {
 
     insta::assert_debug_snapshot!(err);
 }
+
+#[test]
+fn test_captures() {
+    let parser = Parser::new();
+
+    let parsed = parser
+        .parse_str("(?<year>[0123456789]+)-(?:x|(y))-((?<day>d)(e))")
+        .expect("failed to parse");
+
+    assert_eq!(parsed.capture_count(), 5);
+    insta::assert_debug_snapshot!(parsed.captures());
+}
+
+#[test]
+fn test_duplicate_group_name_err() {
+    let parser = Parser::new();
+
+    let err = parser
+        .parse_str("(?<a>foo)(?<a>bar)")
+        .expect_err("expected parse failure");
+
+    insta::assert_debug_snapshot!(err);
+}
+
+#[test]
+fn test_parse_alternative_group_syntax() {
+    let parser = Parser::new();
+
+    let parsed = parser
+        .parse_str("(?P<first>foo)(?'second'bar)(?<_3rd>baz)(?P=second)")
+        .expect("failed to parse");
+
+    insta::assert_debug_snapshot!(parsed);
+}
+
+#[test]
+fn test_invalid_group_name_err() {
+    let parser = Parser::new();
+
+    let err = parser.parse_str("(?<1st>foo)").expect_err("expected parse failure");
+
+    insta::assert_debug_snapshot!(err);
+}
+
+#[test]
+fn test_byte_in_set_err() {
+    let parser = Parser::new();
+
+    let err = parser.parse_str("[a\\xE9]").expect_err("expected parse failure");
+
+    insta::assert_debug_snapshot!(err);
+}
+
+#[test]
+fn test_unknown_backreference_err() {
+    let parser = Parser::new();
+
+    let err = parser
+        .parse_str("(?P<a>foo)(?P=b)")
+        .expect_err("expected parse failure");
+
+    insta::assert_debug_snapshot!(err);
+}
+
+#[test]
+fn test_parse_posix_bre() {
+    let parser = Parser::with_syntax(Syntax::PosixBre);
+
+    let parsed = parser
+        .parse_str("^*a\\(b\\|c+\\)\\{2,3\\}[[:digit:]x-z\\]*(d)$")
+        .expect("failed to parse");
+
+    insta::assert_debug_snapshot!(parsed);
+}
+
+#[test]
+fn test_parse_posix_ere() {
+    let parser = Parser::with_syntax(Syntax::PosixEre);
+
+    let parsed = parser.parse_str("^(a|b+)?[]a-c\\]{2}\\.$").expect("failed to parse");
+
+    insta::assert_debug_snapshot!(parsed);
+}
+
+#[test]
+fn test_parse_glob() {
+    let parser = Parser::with_syntax(Syntax::Glob);
+
+    let parsed = parser.parse_str("src/**/*.{rs,t?ml}[!~]").expect("failed to parse");
+
+    insta::assert_debug_snapshot!(parsed);
+}
+
+#[test]
+fn test_syntax_err_points_at_original_pattern() {
+    let parser = Parser::with_syntax(Syntax::PosixBre);
+
+    let err = parser
+        .parse_str("ab\\(c\\)[[:nope:]]")
+        .expect_err("expected parse failure");
+
+    insta::assert_debug_snapshot!(err);
+}
+
+#[test]
+fn test_nesting_limit_err() {
+    let parser = Parser::new().max_nesting(3);
+
+    parser.parse_str("(((a)))").expect("failed to parse");
+    let err = parser.parse_str("((((a))))").expect_err("expected parse failure");
+
+    insta::assert_debug_snapshot!(err);
+}
+
+#[test]
+fn test_repetition_limit_err() {
+    let parser = Parser::new();
+
+    let err = parser.parse_str("a{99999999999}").expect_err("expected parse failure");
+    insta::assert_debug_snapshot!(err);
+
+    let err = Parser::new()
+        .max_repetition(10)
+        .parse_str("a{1,11}")
+        .expect_err("expected parse failure");
+    insta::assert_debug_snapshot!(err);
+}
+
+#[test]
+fn test_node_limit_err() {
+    let parser = Parser::new().max_nodes(4);
+
+    parser.parse_str("ab*c+").expect("failed to parse");
+    let err = parser.parse_str("ab*c+d").expect_err("expected parse failure");
+
+    insta::assert_debug_snapshot!(err);
+}
+
+#[test]
+fn test_set_range_limit() {
+    let parser = Parser::with_syntax(Syntax::PosixEre);
+
+    parser.parse_str("[一-龥]").expect("failed to parse");
+    parser.parse_str("[\u{100}-\u{FFFF}]").expect("failed to parse");
+
+    let parser = Parser::with_syntax(Syntax::PosixEre).max_set_range(10);
+
+    parser.parse_str("[0-9]").expect("failed to parse");
+    let err = parser.parse_str("[0-9a-z]").expect_err("expected parse failure");
+
+    insta::assert_debug_snapshot!(err);
+}
+
+#[test]
+fn test_unterminated_repetition_range_err() {
+    let parser = Parser::new();
+
+    let err = parser.parse_str("a{2").expect_err("expected parse failure");
+
+    insta::assert_debug_snapshot!(err);
+}
+
+#[test]
+fn test_invalid_repetition_range_err() {
+    let parser = Parser::new();
+
+    let err = parser.parse_str("ab{5,2}").expect_err("expected parse failure");
+
+    insta::assert_debug_snapshot!(err);
+}
+
+#[test]
+fn test_backreference_to_open_group_err() {
+    let parser = Parser::new();
+
+    let err = parser.parse_str("(?P<a>x(?P=a))").expect_err("expected parse failure");
+
+    insta::assert_debug_snapshot!(err);
+}
+
+#[test]
+fn test_parse_empty_alternatives() {
+    let parser = Parser::new();
+
+    let parsed = parser.parse_str("(|a)|(a|)|b||").expect("failed to parse");
+
+    insta::assert_debug_snapshot!(parsed);
+}
+
+#[test]
+fn test_parse_empty_group() {
+    let parser = Parser::new();
+
+    let parsed = parser.parse_str("a()(?:)b").expect("failed to parse");
+
+    insta::assert_debug_snapshot!(parsed);
+}
+
+#[test]
+fn test_warnings() {
+    let parser = Parser::new();
+
+    let warnings = ["^*a", "[aab]c", "a$b", "(a$)b", "a{0}$", "a||b", "(|c)", "ok"]
+        .iter()
+        .map(|pattern| {
+            let parsed = parser.parse_str(pattern).expect("failed to parse");
+
+            (
+                *pattern,
+                parsed.warnings().iter().map(ToString::to_string).collect::<Vec<_>>(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    insta::assert_debug_snapshot!(warnings);
+}
+
+#[test]
+fn test_parse_iter() {
+    let parser = Parser::new();
+
+    let chars = vec!['h', '(', 'é', 'l', ')', '+', 'o'];
+    let parsed = parser.parse_iter(chars).expect("failed to parse");
+    insta::assert_debug_snapshot!(parsed);
+
+    // The error doesn't borrow the pattern, so it can outlive it.
+    let err = {
+        let pattern = String::from("ab[c");
+        parser.parse_iter(pattern.chars()).expect_err("expected parse failure")
+    };
+    insta::assert_debug_snapshot!(err);
+
+    let err = parser
+        .parse_str("a{2")
+        .expect_err("expected parse failure")
+        .into_owned();
+    assert_eq!(err.position, 4);
+}
+
+#[test]
+fn test_parse_reader() {
+    let parser = Parser::new();
+
+    let parsed = parser
+        .parse_reader("(?<n>é[bc])+$".as_bytes())
+        .expect("failed to parse");
+    insta::assert_debug_snapshot!(parsed);
+
+    let err = parser
+        .parse_reader("ab)".as_bytes())
+        .expect_err("expected parse failure");
+    insta::assert_debug_snapshot!(err);
+
+    // Invalid UTF-8 is reported as a read error rather than whatever parse error the truncated pattern causes.
+    let err = parser.parse_reader(&b"(ab\xff"[..]).expect_err("expected read failure");
+    assert!(matches!(err, ReadParseError::Io(_)), "{:?}", err);
+}
@@ -0,0 +1,219 @@
+use std::thread;
+
+use rustex::{executor::SearchMode, parser::Syntax, Regex, RegexBuilder};
+
+fn assert_shareable<T: Send + Sync + Clone>() {}
+
+#[test]
+fn test_regex_is_shareable() {
+    assert_shareable::<Regex>();
+
+    let regex = Regex::new("b[aeiou]r").expect("should compile");
+    thread::scope(|scope| {
+        for input in ["bar", "foo bir", "ber baz"] {
+            let regex = regex.clone();
+            scope.spawn(move || assert!(regex.is_match(input), "input: {}", input));
+        }
+    });
+}
+
+#[test]
+fn test_find() {
+    let regex = Regex::new("wo+rld").expect("should compile");
+
+    let found = regex.find("hello wooorld!").expect("expected match");
+    assert_eq!((found.start(), found.end()), (6, 13));
+    assert_eq!(found.as_str(), "wooorld");
+
+    assert!(regex.find("hello there").is_none());
+    assert!(!regex.is_match("hello there"));
+}
+
+#[test]
+fn test_captures() {
+    let regex = Regex::new("(?<key>[abc]+)=(v)?(?<value>[xyz]+)").expect("should compile");
+
+    assert_eq!(regex.captures_len(), 4);
+    assert_eq!(
+        regex.capture_names().collect::<Vec<_>>(),
+        vec![None, Some("key"), None, Some("value")]
+    );
+
+    let captures = regex.captures("ab=zy").expect("expected match");
+    assert_eq!(captures.len(), 4);
+    assert_eq!(captures.get(0).map(|m| m.as_str()), Some("ab=zy"));
+    assert_eq!(captures.name("key").map(|m| m.as_str()), Some("ab"));
+    assert_eq!(captures.get(2), None);
+    assert_eq!(captures.name("value").map(|m| m.range()), Some(3..5));
+}
+
+#[test]
+fn test_builder() {
+    let regex = RegexBuilder::new("a\\{2\\}b")
+        .syntax(Syntax::PosixBre)
+        .build()
+        .expect("should compile");
+    assert_eq!(regex.find("caab").map(|m| m.as_str()), Some("aab"));
+
+    let err = RegexBuilder::new("a{20}")
+        .max_repetition(10)
+        .build()
+        .expect_err("expected parse failure");
+    assert_eq!(err.position, 5);
+
+    let unoptimized = RegexBuilder::new("abc|abd")
+        .optimize(false)
+        .build()
+        .expect("should compile");
+    assert_eq!(unoptimized.find("xabd").map(|m| m.range()), Some(1..4));
+    assert_eq!(unoptimized.as_str(), "abc|abd");
+}
+
+#[test]
+fn test_find_iter() {
+    let regex = Regex::new("b[aeiou]r").expect("should compile");
+
+    let found = regex
+        .find_iter("bar ber bxr bir")
+        .map(|m| m.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(found, vec!["bar", "ber", "bir"]);
+
+    assert_eq!(regex.find_iter("nothing here").count(), 0);
+}
+
+#[test]
+fn test_find_iter_alternate_prefixes() {
+    // Each search picks up where the last one left off, so the next occurrence of every prefix has to be tracked.
+    let regex = Regex::new("foo|ba[rz]|x.ERROR").expect("should compile");
+
+    let found = regex
+        .find_iter("bar foo baz foofoo bax xxERROR barbar")
+        .map(|m| m.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(found, vec!["bar", "foo", "baz", "foo", "foo", "xxERROR", "bar", "bar"]);
+
+    let found = regex
+        .find_overlapping("fobarfoo")
+        .map(|m| m.range())
+        .collect::<Vec<_>>();
+    assert_eq!(found, vec![2..5, 5..8]);
+}
+
+#[test]
+fn test_find_iter_empty_matches() {
+    let ranges = |pattern: &str, input: &str| {
+        Regex::new(pattern)
+            .expect("should compile")
+            .find_iter(input)
+            .map(|m| m.range())
+            .collect::<Vec<_>>()
+    };
+
+    // An empty match right after a match is skipped, but later ones aren't.
+    assert_eq!(ranges("a*", "aab"), vec![0..2, 3..3]);
+    assert_eq!(ranges("x?", "ab"), vec![0..0, 1..1, 2..2]);
+    assert_eq!(ranges("x*", "é"), vec![0..0, 2..2]);
+}
+
+#[test]
+fn test_captures_iter() {
+    let regex = Regex::new("(?<key>[abc]+)=(?<value>[xyz]+)").expect("should compile");
+
+    let pairs = regex
+        .captures_iter("a=x, bc=zy, d=x, c=z")
+        .map(|captures| {
+            (
+                captures.name("key").map(|m| m.as_str()),
+                captures.name("value").map(|m| m.as_str()),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        pairs,
+        vec![(Some("a"), Some("x")), (Some("bc"), Some("zy")), (Some("c"), Some("z"))]
+    );
+}
+
+#[test]
+fn test_find_overlapping() {
+    let ranges = |pattern: &str, input: &str, all_ends: bool| {
+        Regex::new(pattern)
+            .expect("should compile")
+            .find_overlapping(input)
+            .all_ends(all_ends)
+            .map(|m| m.range())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(ranges("aa", "aaaa", false), vec![0..2, 1..3, 2..4]);
+    assert_eq!(ranges("a[ct]g", "acgatgx", false), vec![0..3, 3..6]);
+    assert_eq!(ranges("a+", "baa", false), vec![1..3, 2..3]);
+    assert_eq!(ranges("a+", "baa", true), vec![1..2, 1..3, 2..3]);
+    assert_eq!(ranges("a|ab|abc", "abc", true), vec![0..1, 0..2, 0..3]);
+}
+
+#[test]
+fn test_find_at() {
+    let regex = Regex::new("b[aeiou]r").expect("should compile");
+    assert_eq!(regex.find_at("bar ber", 1).map(|m| m.range()), Some(4..7));
+    assert_eq!(regex.find_at("bar ber", 5), None);
+    assert_eq!(regex.find_at("bar ber", 10), None);
+
+    // A search starting inside a char starts at the next one instead.
+    assert_eq!(regex.find_at("ébar", 1).map(|m| m.range()), Some(2..5));
+
+    // `^` is relative to the whole input, not where the search starts.
+    let regex = Regex::new("^b").expect("should compile");
+    assert_eq!(regex.find_at("bb", 0).map(|m| m.range()), Some(0..1));
+    assert_eq!(regex.find_at("bb", 1), None);
+
+    let regex = Regex::new("(?<num>[0123456789]+)").expect("should compile");
+    let captures = regex.captures_at("a1 b22", 2).expect("expected match");
+    assert_eq!(captures.name("num").map(|m| m.as_str()), Some("22"));
+    assert!(regex.captures_at("a1 b22", 7).is_none());
+
+    // An empty match can still be found at the very end.
+    let regex = Regex::new("x*").expect("should compile");
+    assert_eq!(regex.find_at("ab", 2).map(|m| m.range()), Some(2..2));
+    assert_eq!(regex.find_at("ab", 3), None);
+}
+
+#[test]
+fn test_anchored() {
+    let regex = RegexBuilder::new("[abc]+|[0123456789]+| +")
+        .mode(SearchMode::Anchored)
+        .build()
+        .expect("should compile");
+
+    let tokenize = |input: &'static str| {
+        let mut tokens = vec![];
+        let mut pos = 0;
+        while let Some(token) = regex.find_at(input, pos) {
+            tokens.push(token.as_str());
+            pos = token.end();
+        }
+
+        (tokens, pos)
+    };
+
+    assert_eq!(tokenize("ab 12  c"), (vec!["ab", " ", "12", "  ", "c"], 8));
+
+    // Nothing matches at the `x`, so tokenizing stops there instead of skipping ahead.
+    assert_eq!(tokenize("ab x 12"), (vec!["ab", " "], 3));
+}
+
+#[test]
+fn test_full_match() {
+    let regex = RegexBuilder::new("a|ab")
+        .mode(SearchMode::FullMatch)
+        .build()
+        .expect("should compile");
+
+    // The first alternative matches but doesn't reach the end, so the second one is used.
+    assert_eq!(regex.find("ab").map(|m| m.range()), Some(0..2));
+    assert_eq!(regex.find_at("xab", 1).map(|m| m.range()), Some(1..3));
+    assert!(!regex.is_match("abc"));
+    assert!(!regex.is_match("xab"));
+}
@@ -1,16 +1,24 @@
+#[cfg(feature = "async")]
 use std::sync;
 
 use rustex::{executor::Executor, parser::Parser, replace::ReplaceSpec};
+#[cfg(feature = "async")]
 use tracing_subscriber::EnvFilter;
 
-const TEST_INIT: sync::Once = sync::Once::new();
+#[cfg(feature = "async")]
+static TEST_INIT: sync::Once = sync::Once::new();
 
+#[cfg(feature = "async")]
 fn init_tests() {
     TEST_INIT.call_once(|| {
-        tracing_subscriber::fmt().with_env_filter(EnvFilter::from_default_env()).with_test_writer().init();
+        tracing_subscriber::fmt()
+            .with_env_filter(EnvFilter::from_default_env())
+            .with_test_writer()
+            .init();
     });
 }
 
+#[cfg(feature = "async")]
 async fn run_test(pattern: &str, input: &str, replace_pattern: &str) -> Result<String, Box<String>> {
     init_tests();
 
@@ -22,14 +30,15 @@ async fn run_test(pattern: &str, input: &str, replace_pattern: &str) -> Result<S
         .exec(&parse_res, input)
         .await
         .map_err(|err| format!("failed to exec: {:?}", err))?
-        .ok_or_else(|| format!("empty exec result"))?;
+        .ok_or_else(|| "empty exec result".to_string())?;
 
     let spec = ReplaceSpec::parse_str(replace_pattern);
 
     spec.perform_replace(input, &exec_res)
-        .ok_or_else(|| Box::new(format!("failed to perform replace or empty replace pattern")))
+        .ok_or_else(|| Box::new("failed to perform replace or empty replace pattern".to_string()))
 }
 
+#[cfg(feature = "async")]
 #[tokio::test]
 async fn test_replace_basic() {
     let result = run_test("(he)llo wo(r)ld!", "hello world!", "$1llo $2ust!")
@@ -38,3 +47,25 @@ async fn test_replace_basic() {
 
     insta::assert_debug_snapshot!(result);
 }
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn test_replace_named_groups_are_numbered() {
+    let result = run_test("(?<first>he)llo (wo)rld!", "hello world!", "$2 ${first} [$0] $$ $9")
+        .await
+        .unwrap();
+
+    assert_eq!(result, "wo he [hello world!] $$ $9");
+}
+
+#[test]
+fn test_replace_bytes() {
+    let parse_res = Parser::new().parse_str("(?<id>[0123456789]+)").unwrap();
+    let input = b"\xFFid=42\xFE";
+
+    let exec_res = Executor::new().exec_bytes(&parse_res, input).unwrap().unwrap();
+
+    let replaced = ReplaceSpec::parse_str("<${id}>").perform_replace_bytes(input, &exec_res);
+
+    assert_eq!(replaced, Some(b"<42>".to_vec()));
+}
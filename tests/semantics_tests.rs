@@ -0,0 +1,96 @@
+use std::ops::Range;
+
+use rustex::{executor::MatchSemantics, RegexBuilder};
+
+fn find(semantics: MatchSemantics, pattern: &str, input: &str) -> Option<Range<usize>> {
+    let find = |optimize: bool| {
+        RegexBuilder::new(pattern)
+            .semantics(semantics)
+            .optimize(optimize)
+            .build()
+            .expect("should compile")
+            .find(input)
+            .map(|m| m.range())
+    };
+
+    // The optimizer has to keep the priority of every alternative.
+    let found = find(true);
+    assert_eq!(found, find(false), "'{}' against '{}'", pattern, input);
+
+    found
+}
+
+fn assert_conforms(semantics: MatchSemantics, cases: &[(&str, &str, Option<Range<usize>>)]) {
+    for (pattern, input, expected) in cases {
+        assert_eq!(
+            find(semantics, pattern, input),
+            *expected,
+            "'{}' against '{}' ({:?})",
+            pattern,
+            input,
+            semantics
+        );
+    }
+}
+
+#[test]
+fn test_leftmost_first() {
+    assert_conforms(
+        MatchSemantics::LeftmostFirst,
+        &[
+            ("a|ab", "ab", Some(0..1)),
+            ("ab|a", "ab", Some(0..2)),
+            ("sam|samwise", "samwise", Some(0..3)),
+            ("a*", "aaa", Some(0..3)),
+            ("a*?", "aaa", Some(0..0)),
+            ("a+?", "aaa", Some(0..1)),
+            ("a{1,3}", "aaaa", Some(0..3)),
+            ("a?ab", "ab", Some(0..2)),
+            ("(a|ab)(c|bcd)", "abcd", Some(0..4)),
+            ("b|bcd", "abcd", Some(1..2)),
+            ("^(a|b|c)+$", "abc", Some(0..3)),
+            ("(|a)+$", "aa", Some(0..2)),
+            ("x(?:ab|a)*y", "xaabay", Some(0..6)),
+            ("(?:(?:ac)*|.){2}ac", "cacac", Some(0..3)),
+        ],
+    );
+}
+
+#[test]
+fn test_leftmost_longest() {
+    assert_conforms(
+        MatchSemantics::LeftmostLongest,
+        &[
+            ("a|ab", "ab", Some(0..2)),
+            ("ab|a", "ab", Some(0..2)),
+            ("sam|samwise", "samwise", Some(0..7)),
+            ("a*", "aaa", Some(0..3)),
+            ("a*?", "aaa", Some(0..3)),
+            ("a+?", "aaa", Some(0..3)),
+            ("a{1,3}", "aaaa", Some(0..3)),
+            ("(a|ab)(c|bcd)", "abcd", Some(0..4)),
+            ("b|bcd", "abcd", Some(1..4)),
+            ("^(a|b|c)+$", "abc", Some(0..3)),
+            ("(|a)+$", "aa", Some(0..2)),
+            ("x(?:ab|a)*y", "xaabay", Some(0..6)),
+        ],
+    );
+}
+
+#[test]
+fn test_groups_follow_priority() {
+    let captures = |semantics: MatchSemantics| {
+        let regex = RegexBuilder::new("(a|ab)(b?)")
+            .semantics(semantics)
+            .build()
+            .expect("should compile");
+
+        let captures = regex.captures("ab").expect("expected match");
+        captures.iter().map(|m| m.map(|m| m.range())).collect::<Vec<_>>()
+    };
+
+    // Both ways of matching `ab` are equally long, so the one tried first wins either way.
+    let expected = vec![Some(0..2), Some(0..1), Some(1..2)];
+    assert_eq!(captures(MatchSemantics::LeftmostFirst), expected);
+    assert_eq!(captures(MatchSemantics::LeftmostLongest), expected);
+}
@@ -0,0 +1,81 @@
+#![cfg(feature = "serde")]
+
+use rustex::{
+    executor::{ExecResult, Executor},
+    parser::{ParseResult, Parser},
+};
+
+const PATTERNS: &[&str] = &[
+    "hello world 123",
+    "he(llo (1(23) wor)ld i am (?<my_group>named) (?:unnamed) groups)",
+    "hel[^lo] (123) w[orld]",
+    "(foo)|((bar)|(baz)qux)",
+    "(foo){0,5}bar{1}o{2,}",
+    "^foo*bar+?(baz)?qu?x.$",
+    "caf\\xE9 \\xFF+",
+];
+
+fn round_trip(pattern: &str) -> (ParseResult, ParseResult) {
+    let parsed = Parser::new().parse_str(pattern).expect("should parse");
+
+    let json = serde_json::to_string(&parsed).expect("should serialize");
+    let deserialized: ParseResult = serde_json::from_str(&json).expect("should deserialize");
+
+    (parsed, deserialized)
+}
+
+#[test]
+fn test_parse_result_round_trip() {
+    for pattern in PATTERNS {
+        let (parsed, deserialized) = round_trip(pattern);
+
+        assert_eq!(
+            format!("{:?}", parsed),
+            format!("{:?}", deserialized),
+            "pattern: {}",
+            pattern
+        );
+    }
+}
+
+#[test]
+fn test_parse_result_schema() {
+    let parsed = Parser::new().parse_str("a(?<b>c)+|[^d]").expect("should parse");
+
+    insta::assert_snapshot!(serde_json::to_string_pretty(&parsed).expect("should serialize"));
+}
+
+#[test]
+fn test_empty_chain_is_rejected() {
+    let res = serde_json::from_str::<ParseResult>(r#"{ "head": [] }"#);
+
+    assert!(res.is_err());
+}
+
+#[test]
+fn test_invalid_graph_is_rejected() {
+    for json in [
+        r#"{ "head": [{ "kind": "or", "value": [] }] }"#,
+        r#"{ "head": [{ "kind": "repetition_range", "value": { "min": 3, "max": 1, "node": [{ "kind": "any" }] } }] }"#,
+        r#"{ "head": [{ "kind": "word", "value": "a" }, { "kind": "backreference", "value": 1 }] }"#,
+    ] {
+        let res = serde_json::from_str::<ParseResult>(json);
+
+        assert!(res.is_err(), "json: {}", json);
+    }
+}
+
+#[test]
+fn test_exec_result_round_trip() {
+    let (_, parsed) = round_trip("(?<one>[^ ]+) (?:world) (?<two>foo) ");
+
+    let res = Executor::new()
+        .exec_sync(&parsed, "hello world foo bar baz")
+        .expect("should exec")
+        .expect("expected exec result");
+
+    let json = serde_json::to_string(&res).expect("should serialize");
+    let deserialized: ExecResult = serde_json::from_str(&json).expect("should deserialize");
+
+    assert_eq!(format!("{:?}", res), format!("{:?}", deserialized));
+}